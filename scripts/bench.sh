@@ -0,0 +1,51 @@
+#!/bin/env bash
+
+# Compare the gsk and gl backends at increasing particle counts.
+# Requires: a release build and a running Wayland compositor (or X11 session)
+#
+# Usage: scripts/bench.sh [type]        (default: confetti)
+#   SOFTWARE=1   force Mesa's llvmpipe software rasterizer
+#   DURATION=N   seconds per run (default: 5)
+#
+# Each run prints one line from `confet --stats`: frames drawn, average fps, and the
+# CPU time per frame spent building render nodes (gsk) or uploading instances (gl).
+# A markdown table of the results follows, ready for the README's
+# "Large particle counts" section.
+
+set -euo pipefail
+
+ROOT="$(git -C "$(dirname "$0")" rev-parse --show-toplevel)"
+CONFET="$ROOT/target/release/confet"
+TYPE="${1:-confetti}"
+DURATION="${DURATION:-5}"
+
+if [[ ! -x "$CONFET" ]]; then
+    echo "confet binary not found — run 'cargo build --release' first"
+    exit 1
+fi
+
+if [[ "${SOFTWARE:-0}" == 1 ]]; then
+    export LIBGL_ALWAYS_SOFTWARE=1
+    echo "using llvmpipe (LIBGL_ALWAYS_SOFTWARE=1)"
+fi
+
+RENDERER="${LIBGL_ALWAYS_SOFTWARE:+llvmpipe}"
+RENDERER="${RENDERER:-hardware}"
+declare -A RESULT
+
+for count in 10000 50000 100000; do
+    for backend in gsk gl; do
+        line="$("$CONFET" "$TYPE" --particles "$count" --duration "$DURATION" --backend "$backend" --stats 2>&1 \
+            | grep ' particles, ' || echo "$count particles, $backend: failed")"
+        echo "$line"
+        # "(58.3 fps), draw 4.21 ms/frame" -> "58.3 fps, 4.21 ms"
+        RESULT[$count,$backend]="$(sed -nE 's/.*\(([0-9.]+) fps\), draw ([0-9.]+) ms.*/\1 fps, \2 ms/p' <<<"$line")"
+    done
+done
+
+echo
+echo "| Particles ($RENDERER) | gsk | gl |"
+echo "|---|---|---|"
+for count in 10000 50000 100000; do
+    echo "| $count | ${RESULT[$count,gsk]:-failed} | ${RESULT[$count,gl]:-failed} |"
+done
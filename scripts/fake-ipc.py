@@ -0,0 +1,96 @@
+#!/usr/bin/env python3
+"""Serve canned compositor IPC replies on a Unix socket, for testing --monitor focused/pointer
+and --origin/--region window.
+
+Usage:
+    scripts/fake-ipc.py sway DIR        # prints: export SWAYSOCK=DIR/sway.sock
+    scripts/fake-ipc.py hyprland DIR    # prints the HYPRLAND_INSTANCE_SIGNATURE/XDG_RUNTIME_DIR exports
+
+Edit the JSON below to match the connector names of your monitors.
+"""
+
+import json
+import os
+import socket
+import struct
+import sys
+
+SWAY_OUTPUTS = [
+    {"name": "DP-1", "focused": False, "rect": {"x": 0, "y": 0, "width": 2560, "height": 1440}},
+    {"name": "DP-2", "focused": True, "rect": {"x": 2560, "y": 0, "width": 2560, "height": 1440}},
+]
+
+SWAY_TREE = {
+    "type": "root", "focused": False, "rect": {"x": 0, "y": 0, "width": 5120, "height": 1440},
+    "nodes": [{
+        "type": "output", "name": "DP-2", "focused": False,
+        "nodes": [{
+            "type": "workspace", "name": "1", "focused": False,
+            "nodes": [
+                {"type": "con", "name": "editor", "focused": False,
+                 "rect": {"x": 2560, "y": 0, "width": 1280, "height": 1440}, "nodes": []},
+                {"type": "con", "name": "terminal", "focused": True,
+                 "rect": {"x": 3840, "y": 720, "width": 1280, "height": 720}, "nodes": []},
+            ],
+            "floating_nodes": [],
+        }],
+    }],
+}
+
+HYPR_MONITORS = [
+    {"name": "DP-1", "focused": False, "x": 0, "y": 0, "width": 2560, "height": 1440, "scale": 1.0},
+    {"name": "DP-2", "focused": True, "x": 2560, "y": 0, "width": 3840, "height": 2160, "scale": 1.5},
+]
+HYPR_CURSOR = {"x": 1200, "y": 700}
+HYPR_WINDOW = {"class": "foot", "at": [200, 150], "size": [900, 600]}
+
+
+def serve_sway(path):
+    srv = socket.socket(socket.AF_UNIX)
+    srv.bind(path)
+    srv.listen()
+    while True:
+        conn, _ = srv.accept()
+        with conn:
+            header = conn.recv(14)
+            length, kind = struct.unpack("=II", header[6:14])
+            if length:
+                conn.recv(length)
+            body = json.dumps({3: SWAY_OUTPUTS, 4: SWAY_TREE}.get(kind, {})).encode()
+            conn.sendall(b"i3-ipc" + struct.pack("=II", len(body), kind) + body)
+
+
+def serve_hyprland(path):
+    srv = socket.socket(socket.AF_UNIX)
+    srv.bind(path)
+    srv.listen()
+    while True:
+        conn, _ = srv.accept()
+        with conn:
+            cmd = conn.recv(1024).decode()
+            reply = {
+                "j/monitors": HYPR_MONITORS,
+                "j/cursorpos": HYPR_CURSOR,
+                "j/activewindow": HYPR_WINDOW,
+            }.get(cmd, {})
+            conn.sendall(json.dumps(reply).encode())
+
+
+def main():
+    if len(sys.argv) != 3 or sys.argv[1] not in ("sway", "hyprland"):
+        print(__doc__)
+        sys.exit(1)
+    kind, root = sys.argv[1], os.path.abspath(sys.argv[2])
+    if kind == "sway":
+        path = os.path.join(root, "sway.sock")
+        print(f"export SWAYSOCK={path}", flush=True)
+        serve_sway(path)
+    else:
+        sock_dir = os.path.join(root, "hypr", "fake")
+        os.makedirs(sock_dir, exist_ok=True)
+        print(f"export XDG_RUNTIME_DIR={root} HYPRLAND_INSTANCE_SIGNATURE=fake", flush=True)
+        serve_hyprland(os.path.join(sock_dir, ".socket.sock"))
+
+
+if __name__ == "__main__":
+    main()
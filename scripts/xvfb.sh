@@ -0,0 +1,50 @@
+#!/bin/env bash
+
+# Run confet on a headless X server to exercise the X11 overlay fallback.
+# Requires: a release build, Xvfb, a compositing manager (picom or xcompmgr) and
+# ImageMagick's `import` for the screenshot
+#
+# Usage: scripts/xvfb.sh [confet args...]     (default: pop --duration 3)
+#   SCREENSHOT=path   where to save a capture taken mid-effect (default: xvfb.png)
+#
+# The capture should show particles over the black root window; a solid black
+# rectangle the size of the screen means the window didn't get an ARGB visual.
+
+set -euo pipefail
+
+ROOT="$(git -C "$(dirname "$0")" rev-parse --show-toplevel)"
+CONFET="$ROOT/target/release/confet"
+SCREENSHOT="${SCREENSHOT:-xvfb.png}"
+DISPLAY_NUM=":99"
+
+if [[ ! -x "$CONFET" ]]; then
+    echo "confet binary not found — run 'cargo build --release' first"
+    exit 1
+fi
+if [[ $# -eq 0 ]]; then
+    set -- pop --duration 3
+fi
+
+Xvfb "$DISPLAY_NUM" -screen 0 1280x720x24 +extension Composite &
+trap 'kill $(jobs -p) 2>/dev/null' EXIT
+sleep 1
+export DISPLAY="$DISPLAY_NUM"
+unset WAYLAND_DISPLAY
+
+if command -v picom >/dev/null; then
+    picom --backend xrender &
+elif command -v xcompmgr >/dev/null; then
+    xcompmgr &
+else
+    echo "no compositing manager found; the overlay won't be transparent"
+fi
+sleep 0.5
+
+GDK_BACKEND=x11 "$CONFET" "$@" &
+CONFET_PID=$!
+sleep 1
+if command -v import >/dev/null; then
+    import -window root "$SCREENSHOT"
+    echo "saved $SCREENSHOT"
+fi
+wait "$CONFET_PID"
@@ -1,10 +1,23 @@
 use crate::config::settings;
+use crate::instanced::Instanced;
 use crate::particle::Particles;
+use crate::types::Backend;
 use gtk4::prelude::*;
 use gtk4::subclass::prelude::*;
-use gtk4::glib;
-use std::cell::RefCell;
-use std::time::Instant;
+use gtk4::{gdk, glib};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Fade-out multiplier at time `t`.
+fn fade_alpha(t: f64) -> f32 {
+    let s = settings();
+    if t > s.duration - s.fade {
+        ((s.duration - t) / s.fade).clamp(0.0, 1.0) as f32
+    } else {
+        1.0
+    }
+}
 
 mod imp {
     use super::*;
@@ -13,11 +26,29 @@ mod imp {
         pub(crate) ps: Particles,
         pub t0: Instant,
         pub last: Instant,
+        pub frames: u32,
+        /// Time spent building render nodes or uploading instances
+        pub draw_time: Duration,
     }
 
     #[derive(Default)]
     pub struct Canvas {
-        pub state: RefCell<Option<State>>,
+        /// Simulation this canvas shows; shared between canvases spanning several monitors
+        pub state: RefCell<Option<Rc<RefCell<State>>>>,
+        /// Top-left of this canvas's slice of the simulation
+        pub offset: Cell<(f32, f32)>,
+        /// Child area and its renderer when drawing through the GL backend
+        pub gl_area: RefCell<Option<gtk4::GLArea>>,
+        pub gl: RefCell<Option<Instanced>>,
+    }
+
+    impl Canvas {
+        pub fn view(&self) -> graphene::Rect {
+            let (x, y) = self.offset.get();
+            let w = self.obj().width() as f32;
+            let h = self.obj().height() as f32;
+            graphene::Rect::new(x, y, w, h)
+        }
     }
 
     #[glib::object_subclass]
@@ -27,20 +58,27 @@ mod imp {
         type ParentType = gtk4::Widget;
     }
 
-    impl ObjectImpl for Canvas {}
+    impl ObjectImpl for Canvas {
+        fn dispose(&self) {
+            if let Some(area) = self.gl_area.take() {
+                area.unparent();
+            }
+        }
+    }
 
     impl WidgetImpl for Canvas {
         fn snapshot(&self, snap: &gtk4::Snapshot) {
-            let s = settings();
+            if self.gl_area.borrow().is_some() {
+                self.parent_snapshot(snap);
+                return;
+            }
             let st = self.state.borrow();
             let Some(state) = st.as_ref() else { return };
+            let mut state = state.borrow_mut();
+            let start = Instant::now();
             let t = state.t0.elapsed().as_secs_f64();
-            let alpha = if t > s.duration - s.fade {
-                ((s.duration - t) / s.fade).clamp(0.0, 1.0) as f32
-            } else {
-                1.0
-            };
-            state.ps.draw(snap, alpha, t);
+            state.ps.draw(snap, fade_alpha(t), t, &self.view());
+            state.draw_time += start.elapsed();
         }
     }
 }
@@ -53,21 +91,44 @@ glib::wrapper! {
 impl Canvas {
     pub fn new() -> Self { glib::Object::builder().build() }
 
-    pub fn start(&self, w: f64, h: f64) {
+    /// Runs a simulation covering `world`, the canvas's rectangle of the desktop layout.
+    pub fn start(&self, world: &gdk::Rectangle) {
+        Self::start_spanning(&[(self.clone(), (0.0, 0.0))], world);
+    }
+
+    /// Runs one simulation over `world` shown across several canvases, each drawing the
+    /// slice at its offset. The first canvas drives the simulation and redraws the rest.
+    pub fn start_spanning(canvases: &[(Canvas, (f32, f32))], world: &gdk::Rectangle) {
+        let (driver, _) = &canvases[0];
         let now = Instant::now();
-        *self.imp().state.borrow_mut() = Some(imp::State {
-            ps: Particles::new(w, h), t0: now, last: now,
-        });
-        self.add_tick_callback(|widget, _| {
+        let state = Rc::new(RefCell::new(imp::State {
+            ps: Particles::new(world, &driver.pango_context()), t0: now, last: now,
+            frames: 0, draw_time: Duration::ZERO,
+        }));
+        for (canvas, offset) in canvases {
+            *canvas.imp().state.borrow_mut() = Some(state.clone());
+            canvas.imp().offset.set(*offset);
+            if settings().backend == Backend::Gl {
+                canvas.attach_gl();
+            }
+        }
+        let peers: Vec<glib::WeakRef<Canvas>> = canvases.iter().map(|(c, _)| c.downgrade()).collect();
+        driver.add_tick_callback(move |widget, _| {
             let s = settings();
-            let canvas: &Canvas = widget.downcast_ref().unwrap();
-            let mut st = canvas.imp().state.borrow_mut();
-            let Some(state) = st.as_mut() else { return glib::ControlFlow::Break };
+            let mut state = state.borrow_mut();
             let now = Instant::now();
             let t = (now - state.t0).as_secs_f64();
             if t > s.duration {
-                drop(st);
-                if let Some(app) = canvas.root()
+                if s.stats {
+                    let ms = state.draw_time.as_secs_f64() * 1000.0 / state.frames.max(1) as f64;
+                    let backend = if s.backend == Backend::Gl { "gl" } else { "gsk" };
+                    eprintln!(
+                        "{} particles, {backend}: {} frames in {t:.2}s ({:.1} fps), draw {ms:.2} ms/frame",
+                        s.particles, state.frames, state.frames as f64 / t,
+                    );
+                }
+                drop(state);
+                if let Some(app) = widget.root()
                     .and_then(|r| r.downcast::<gtk4::Window>().ok())
                     .and_then(|w| w.application())
                 {
@@ -77,10 +138,81 @@ impl Canvas {
             }
             let dt = (now - state.last).as_secs_f64();
             state.last = now;
+            state.frames += 1;
             state.ps.step(dt, t);
-            drop(st);
-            canvas.queue_draw();
+            drop(state);
+            for canvas in peers.iter().filter_map(|p| p.upgrade()) {
+                match canvas.imp().gl_area.borrow().as_ref() {
+                    Some(area) => area.queue_render(),
+                    None => canvas.queue_draw(),
+                }
+            }
             glib::ControlFlow::Continue
         });
     }
+
+    /// Swaps per-particle render nodes for a GL area child that draws every particle in
+    /// one instanced call.
+    fn attach_gl(&self) {
+        let area = gtk4::GLArea::new();
+        area.set_has_depth_buffer(false);
+        self.set_layout_manager(Some(gtk4::BinLayout::new()));
+        area.set_parent(self);
+
+        // The shaders need GL 3.3 or ES 3.0, which GTK's default context doesn't promise
+        area.connect_create_context(|area| {
+            let surface = area.native()?.surface()?;
+            let mut error = None;
+            for (es, major, minor) in [(0, 3, 3), (1, 3, 0)] {
+                let ctx = match surface.create_gl_context() {
+                    Ok(ctx) => ctx,
+                    Err(e) => { error = Some(e); break; }
+                };
+                ctx.set_use_es(es);
+                ctx.set_required_version(major, minor);
+                match ctx.realize() {
+                    Ok(()) => return Some(ctx),
+                    Err(e) => error = Some(e),
+                }
+            }
+            area.set_error(error.as_ref());
+            None
+        });
+
+        let weak = self.downgrade();
+        area.connect_realize(move |area| {
+            let Some(canvas) = weak.upgrade() else { return };
+            area.make_current();
+            if let Some(e) = area.error() {
+                eprintln!("gl backend: {e}");
+                std::process::exit(1);
+            }
+            *canvas.imp().gl.borrow_mut() = Some(Instanced::new(area.uses_es()));
+        });
+
+        let weak = self.downgrade();
+        area.connect_unrealize(move |area| {
+            let Some(canvas) = weak.upgrade() else { return };
+            area.make_current();
+            canvas.imp().gl.take();
+        });
+
+        let weak = self.downgrade();
+        area.connect_render(move |_, _| {
+            let Some(canvas) = weak.upgrade() else { return glib::Propagation::Stop };
+            let imp = canvas.imp();
+            let (st, mut gl) = (imp.state.borrow(), imp.gl.borrow_mut());
+            let (Some(state), Some(gl)) = (st.as_ref(), gl.as_mut()) else {
+                return glib::Propagation::Stop;
+            };
+            let mut state = state.borrow_mut();
+            let start = Instant::now();
+            let t = state.t0.elapsed().as_secs_f64();
+            gl.render(&state.ps, fade_alpha(t), t, &imp.view());
+            state.draw_time += start.elapsed();
+            glib::Propagation::Stop
+        });
+
+        *self.imp().gl_area.borrow_mut() = Some(area);
+    }
 }
@@ -1,7 +1,8 @@
-use crate::types::{AnimType, Shape, DEFAULT_COLORS, ANIM_TYPE_NAMES, BUILTIN_PROFILE_NAMES};
+use crate::types::{AnimType, Axis, Backend, Blend, Form, Origin, Output, Rainbow, Region, Shape, DEFAULT_COLORS, DEFAULT_GLYPHS, ANIM_TYPE_NAMES, BUILTIN_PROFILE_NAMES};
 use clap::{CommandFactory, FromArgMatches, Parser};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
 // ── Global settings ──────────────────────────────────────────────
@@ -23,10 +24,10 @@ pub fn set_settings(s: Settings) {
 pub struct Cli {
     /// Profile name or animation type
     pub profile: Option<String>,
-    /// Animation type (confetti, cannon, pop, fireworks, snow, rain, sparkle, drop)
+    /// Animation type (confetti, cannon, pop, fireworks, snow, rain, sparkle, drop, fountain, meteors, vortex, coins)
     #[arg(short = 't', long = "type")]
     pub anim_type: Option<String>,
-    /// Particle shape (rect, circle, mixed)
+    /// Particle shape (rect, circle, mixed, star, heart, triangle, diamond, ribbon, glyph, sprite, streamer)
     #[arg(short = 's', long)]
     pub shape: Option<String>,
     /// Number of particles
@@ -53,9 +54,96 @@ pub struct Cli {
     /// Fade-out duration in seconds
     #[arg(long)]
     pub fade: Option<f64>,
+    /// Trail length in seconds of motion (0 disables)
+    #[arg(long)]
+    pub trail: Option<f64>,
+    /// Glow halo radius as a multiple of particle size (0 disables)
+    #[arg(long)]
+    pub glow: Option<f64>,
+    /// How overlapping particles combine (normal, additive, screen)
+    #[arg(long)]
+    pub blend: Option<String>,
+    /// Monitor to cover: index, connector name (e.g. DP-2), 'focused', 'pointer' or 'all'
+    #[arg(long)]
+    pub monitor: Option<String>,
+    /// Play one effect across all covered monitors instead of one per monitor
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub span: Option<bool>,
+    /// Confine the effect to part of each monitor: 'x,y,w,h', 'anchor,w,h' (e.g. 'bottom-right,400,300') or 'window'
+    #[arg(long)]
+    pub region: Option<String>,
+    /// Renderer (gsk, gl); gl handles very large particle counts
+    #[arg(long)]
+    pub backend: Option<String>,
+    /// Print frame statistics on exit
+    #[arg(long)]
+    pub stats: bool,
+    /// Number of depth layers for parallax (1 disables, at most 255)
+    #[arg(long)]
+    pub layers: Option<usize>,
+    /// Scale of the nearest and farthest layer (e.g. '1.0,0.3')
+    #[arg(long)]
+    pub depth: Option<String>,
+    /// Blur radius in pixels for the farthest layer (0 disables)
+    #[arg(long)]
+    pub depth_blur: Option<f64>,
+    /// Emission point: 'x,y' in pixels or screen fractions, a keyword like 'center' or 'bottom-left', 'cursor' or 'window'
+    #[arg(long)]
+    pub origin: Option<String>,
+    /// Jet angle in degrees (0 = straight up, negative leans left)
+    #[arg(long, allow_negative_numbers = true)]
+    pub angle: Option<f64>,
+    /// Jet cone width in degrees
+    #[arg(long)]
+    pub cone: Option<f64>,
+    /// Emission rate in particles per second
+    #[arg(long)]
+    pub rate: Option<f64>,
+    /// Vortex angular speed in radians per second (negative spins the other way)
+    #[arg(long, allow_negative_numbers = true)]
+    pub spin: Option<f64>,
+    /// Vortex inward pull in pixels per second (negative flings outward)
+    #[arg(long, allow_negative_numbers = true)]
+    pub pull: Option<f64>,
+    /// Vortex axis (center, vertical)
+    #[arg(long)]
+    pub axis: Option<String>,
+    /// Floor bounciness for coins (0-1)
+    #[arg(long)]
+    pub bounce: Option<f64>,
+    /// Twinkling backdrop stars behind meteors (0 disables)
+    #[arg(long)]
+    pub stars: Option<usize>,
+    /// Text for particles to assemble into, hold, then scatter
+    #[arg(long)]
+    pub text: Option<String>,
+    /// PNG or SVG image whose pixels particles take their positions and colors from
+    #[arg(long)]
+    pub image: Option<String>,
+    /// How text or image particles form (assemble, burst)
+    #[arg(long)]
+    pub form: Option<String>,
+    /// Comma-separated text or emoji for the glyph shape (e.g. '🎉,⭐,🚀')
+    #[arg(long, value_delimiter = ',')]
+    pub glyphs: Option<Vec<String>>,
+    /// Comma-separated PNG or SVG files drawn by the sprite shape
+    #[arg(long, value_delimiter = ',')]
+    pub sprites: Option<Vec<String>>,
+    /// Tint sprites with the palette colors
+    #[arg(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    pub tint: Option<bool>,
     /// Comma-separated hex colors (e.g. '#ff0000,#00ff00,#0000ff')
     #[arg(short, long, value_delimiter = ',')]
     pub colors: Option<Vec<String>>,
+    /// Comma-separated hex colors each particle fades through over its lifetime
+    #[arg(long, value_delimiter = ',')]
+    pub gradient: Option<Vec<String>>,
+    /// Hue cycling (time, position)
+    #[arg(long)]
+    pub rainbow: Option<String>,
+    /// Hue cycling speed in degrees per second
+    #[arg(long, allow_negative_numbers = true)]
+    pub hue_speed: Option<f64>,
     /// Create default config file at ~/.config/confet/config.toml
     #[arg(long)]
     pub init: bool,
@@ -81,11 +169,24 @@ pub fn parse_cli(file: &FileConfig) -> Cli {
 
 // ── Config structs ───────────────────────────────────────────────
 
+/// A `shapes` table entry: a bare weight, or a weight with its own size range and colors
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ShapeEntry {
+    Weight(f64),
+    Detailed {
+        weight: f64,
+        size: Option<[f32; 2]>,
+        colors: Option<Vec<String>>,
+    },
+}
+
 #[derive(Deserialize, Default, Clone)]
 pub struct ProfileConfig {
     #[serde(rename = "type")]
     pub anim_type: Option<String>,
     pub shape: Option<String>,
+    pub shapes: Option<HashMap<String, ShapeEntry>>,
     pub particles: Option<usize>,
     pub duration: Option<f64>,
     pub gravity: Option<f64>,
@@ -94,7 +195,35 @@ pub struct ProfileConfig {
     pub speed_max: Option<f64>,
     pub spread: Option<f64>,
     pub fade: Option<f64>,
+    pub trail: Option<f64>,
+    pub glow: Option<f64>,
+    pub blend: Option<String>,
+    pub monitor: Option<String>,
+    pub span: Option<bool>,
+    pub region: Option<String>,
+    pub backend: Option<String>,
+    pub layers: Option<usize>,
+    pub depth: Option<[f64; 2]>,
+    pub depth_blur: Option<f64>,
+    pub origin: Option<String>,
+    pub angle: Option<f64>,
+    pub cone: Option<f64>,
+    pub rate: Option<f64>,
+    pub spin: Option<f64>,
+    pub pull: Option<f64>,
+    pub axis: Option<String>,
+    pub bounce: Option<f64>,
+    pub stars: Option<usize>,
+    pub text: Option<String>,
+    pub image: Option<String>,
+    pub form: Option<String>,
+    pub glyphs: Option<Vec<String>>,
+    pub sprites: Option<Vec<String>>,
+    pub tint: Option<bool>,
     pub colors: Option<Vec<String>>,
+    pub gradient: Option<Vec<String>>,
+    pub rainbow: Option<String>,
+    pub hue_speed: Option<f64>,
 }
 
 #[derive(Deserialize, Default)]
@@ -102,6 +231,7 @@ pub struct FileConfig {
     #[serde(rename = "type")]
     pub anim_type: Option<String>,
     pub shape: Option<String>,
+    pub shapes: Option<HashMap<String, ShapeEntry>>,
     pub particles: Option<usize>,
     pub duration: Option<f64>,
     pub gravity: Option<f64>,
@@ -110,14 +240,52 @@ pub struct FileConfig {
     pub speed_max: Option<f64>,
     pub spread: Option<f64>,
     pub fade: Option<f64>,
+    pub trail: Option<f64>,
+    pub glow: Option<f64>,
+    pub blend: Option<String>,
+    pub monitor: Option<String>,
+    pub span: Option<bool>,
+    pub region: Option<String>,
+    pub backend: Option<String>,
+    pub layers: Option<usize>,
+    pub depth: Option<[f64; 2]>,
+    pub depth_blur: Option<f64>,
+    pub origin: Option<String>,
+    pub angle: Option<f64>,
+    pub cone: Option<f64>,
+    pub rate: Option<f64>,
+    pub spin: Option<f64>,
+    pub pull: Option<f64>,
+    pub axis: Option<String>,
+    pub bounce: Option<f64>,
+    pub stars: Option<usize>,
+    pub text: Option<String>,
+    pub image: Option<String>,
+    pub form: Option<String>,
+    pub glyphs: Option<Vec<String>>,
+    pub sprites: Option<Vec<String>>,
+    pub tint: Option<bool>,
     pub colors: Option<Vec<String>>,
+    pub gradient: Option<Vec<String>>,
+    pub rainbow: Option<String>,
+    pub hue_speed: Option<f64>,
     #[serde(default)]
     pub profiles: HashMap<String, ProfileConfig>,
 }
 
+/// One resolved entry of a weighted shape mix
+pub struct ShapeMix {
+    pub shape: Shape,
+    pub weight: f64,
+    pub size: Option<[f32; 2]>,
+    pub colors: Option<Vec<[f32; 3]>>,
+}
+
 pub struct Settings {
     pub anim_type: AnimType,
     pub shape: Shape,
+    /// Weighted shape mix; empty means every particle uses `shape`
+    pub shapes: Vec<ShapeMix>,
     pub particles: usize,
     pub duration: f64,
     pub gravity: f64,
@@ -126,7 +294,39 @@ pub struct Settings {
     pub speed_max: f64,
     pub spread: f64,
     pub fade: f64,
+    pub trail: f64,
+    pub glow: f64,
+    pub blend: Blend,
+    pub monitor: Output,
+    pub span: bool,
+    pub region: Option<Region>,
+    pub backend: Backend,
+    pub stats: bool,
+    pub layers: usize,
+    /// Scale of the nearest and farthest layer, applied to size, speed and opacity
+    pub depth: [f64; 2],
+    pub depth_blur: f64,
+    pub origin: Option<Origin>,
+    pub angle: f64,
+    pub cone: f64,
+    pub rate: f64,
+    pub spin: f64,
+    pub pull: f64,
+    pub axis: Axis,
+    pub bounce: f64,
+    /// Backdrop stars drawn behind meteors
+    pub stars: usize,
+    pub text: Option<String>,
+    pub image: Option<PathBuf>,
+    pub form: Form,
+    pub glyphs: Vec<String>,
+    pub sprites: Vec<PathBuf>,
+    pub tint: bool,
     pub colors: Vec<[f32; 3]>,
+    /// Colors each particle fades through over its lifetime; empty keeps `colors` fixed
+    pub gradient: Vec<[f32; 3]>,
+    pub rainbow: Option<Rainbow>,
+    pub hue_speed: f64,
 }
 
 // ── Color parsing ────────────────────────────────────────────────
@@ -140,6 +340,27 @@ fn parse_hex_color(s: &str) -> Option<[f32; 3]> {
     Some([r, g, b])
 }
 
+fn parse_depth(s: &str) -> Option<[f64; 2]> {
+    let (near, far) = s.split_once(',')?;
+    Some([near.trim().parse().ok()?, far.trim().parse().ok()?])
+}
+
+fn parse_shapes(input: &HashMap<String, ShapeEntry>) -> Vec<ShapeMix> {
+    input.iter()
+        .filter_map(|(name, entry)| {
+            let shape = Shape::from_str(name)?;
+            let (weight, size, colors) = match entry {
+                ShapeEntry::Weight(w) => (*w, None, None),
+                ShapeEntry::Detailed { weight, size, colors } => {
+                    (*weight, *size, colors.as_deref().map(parse_colors))
+                }
+            };
+            let size = size.filter(|[lo, hi]| *lo > 0.0 && lo <= hi);
+            (weight > 0.0).then_some(ShapeMix { shape, weight, size, colors })
+        })
+        .collect()
+}
+
 fn parse_colors(input: &[String]) -> Vec<[f32; 3]> {
     let parsed: Vec<_> = input.iter().filter_map(|s| parse_hex_color(s.trim())).collect();
     if parsed.is_empty() { DEFAULT_COLORS.to_vec() } else { parsed }
@@ -209,10 +430,28 @@ speed_min = 900
 speed_max = 2500
 spread = 150
 fade = 0.4
+# trail = 0.05                  # streak behind each particle, in seconds of motion
+# glow = 2.5                    # soft halo radius, as a multiple of particle size
+# blend = "additive"            # normal, additive, screen
+# monitor = "all"               # index, connector name (e.g. "DP-2"), "focused", "pointer" or "all"
+# span = true                   # one effect across all covered monitors
+# region = "bottom-right,480,320"   # only play inside "x,y,w,h", "anchor,w,h" or "window"
+# backend = "gl"                # gsk, gl (instanced GL for very large particle counts)
+# layers = 3                    # depth layers for parallax; far layers are smaller, slower, fainter
+# depth = [1.0, 0.35]           # scale of the nearest and farthest layer
+# depth_blur = 2.0              # blur radius in pixels for the farthest layer
+# origin = "cursor"             # "x,y" in pixels or fractions, center, bottom-left, ..., cursor or window
+# stars = 200                   # twinkling backdrop stars behind meteors
 colors = ["#ff2d87", "#2d8cff", "#2dff6d", "#ffd02d", "#a12dff", "#ff6b2d", "#2dfff6", "#ff2dca"]
-
-# Available types: confetti, cannon, pop, fireworks, snow, rain, sparkle, drop
-# Available shapes: rect, circle, mixed
+# gradient = ["#ffffff", "#ffa500", "#802020"]   # colors over each particle's lifetime
+# rainbow = "time"              # hue cycling: time, position
+# hue_speed = 120               # degrees per second
+
+# Available types: confetti, cannon, pop, fireworks, snow, rain, sparkle, drop, fountain, meteors, vortex, coins
+# Available shapes: rect, circle, mixed, star, heart, triangle, diamond, ribbon, glyph, sprite, streamer
+# glyphs = ["🎉", "⭐", "🚀"]   # text or emoji drawn by shape = "glyph"
+# sprites = ["~/.config/confet/sprites/duck.png"]   # images drawn by shape = "sprite"
+# tint = true                   # tint sprites with the colors below
 # Run a profile: confet <name>
 
 [profiles.lava]
@@ -252,10 +491,59 @@ type = "drop"
 particles = 1200
 duration = 3.5
 colors = ["#ff2d87", "#2d8cff", "#2dff6d", "#ffd02d", "#a12dff", "#ff6b2d"]
+
+# Parallax snow: three depth layers, far flakes at a third of the size and speed, softly blurred
+# [profiles.blizzard]
+# type = "snow"
+# particles = 600
+# layers = 3
+# depth = [1.2, 0.35]
+# depth_blur = 2.0
+
+# Fountain jet: angle/cone in degrees, rate in particles/sec
+# [profiles.geyser]
+# type = "fountain"
+# origin = "0.5,1.0"
+# angle = 0
+# cone = 20
+# rate = 400
+
+# Vortex: spin in rad/sec (negative reverses), pull in px/sec (negative flings outward)
+# [profiles.drain]
+# type = "vortex"
+# axis = "center"
+# spin = 4.0
+# pull = 250
+
+# Text: particles fly in, spell it out, hold, then scatter
+# [profiles.shipped]
+# type = "pop"
+# text = "Shipped!"
+# duration = 4.0
+
+# Image: particles take positions and colors from a PNG/SVG's opaque pixels
+# form = "assemble" flies in then scatters, "burst" appears then explodes
+# [profiles.logo]
+# image = "~/.config/confet/logo.svg"
+# form = "burst"
+# particles = 4000
+
+# Weighted shape mix: bare weights, or tables with their own size range and colors
+# [profiles.party]
+# type = "cannon"
+# shapes = { rect = 0.6, circle = 0.2, star = { weight = 0.2, size = [10, 16], colors = ["#ffd700"] } }
 "##;
 
 // ── File config ──────────────────────────────────────────────────
 
+/// Expands a leading `~/` to the home directory.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
 fn config_path() -> Option<std::path::PathBuf> {
     dirs::config_dir().map(|d| d.join("confet").join("config.toml"))
 }
@@ -327,11 +615,28 @@ impl Settings {
             .or_else(|| cli.profile.as_deref().and_then(AnimType::from_str))
             .unwrap_or_default();
 
+        let sprites: Vec<PathBuf> = cli.sprites.or(profile.sprites).or(file.sprites)
+            .unwrap_or_default()
+            .iter()
+            .map(|p| expand_home(p.trim()))
+            .collect();
+
+        // Pointing a profile at sprites is enough to draw them unless a shape is given
         let shape = cli.shape.as_deref()
             .and_then(Shape::from_str)
             .or_else(|| profile.shape.as_deref().and_then(Shape::from_str))
             .or_else(|| file.shape.as_deref().and_then(Shape::from_str))
-            .unwrap_or_else(|| anim_type.default_shape());
+            .unwrap_or_else(|| if sprites.is_empty() { anim_type.default_shape() } else { Shape::Sprite });
+
+        // A single CLI shape beats any mix; otherwise the profile's shape settings beat the file's
+        let shapes = if cli.shape.is_some() {
+            None
+        } else if profile.shapes.is_some() || profile.shape.is_some() {
+            profile.shapes.as_ref()
+        } else {
+            file.shapes.as_ref()
+        };
+        let shapes = shapes.map(parse_shapes).unwrap_or_default();
 
         let (dp, dd, dg, ddr, dsn, dsx, dsp, df) = anim_type.defaults();
 
@@ -351,17 +656,145 @@ impl Settings {
             anim_type.default_colors().to_vec()
         };
 
+        let depth = cli.depth.as_deref()
+            .and_then(parse_depth)
+            .or(profile.depth)
+            .or(file.depth)
+            .filter(|d| d[0] > 0.0 && d[1] > 0.0)
+            .unwrap_or([1.0, 0.35]);
+        let origin = cli.origin.as_deref()
+            .and_then(Origin::from_str)
+            .or_else(|| profile.origin.as_deref().and_then(Origin::from_str))
+            .or_else(|| file.origin.as_deref().and_then(Origin::from_str));
+
+        let axis = cli.axis.as_deref()
+            .and_then(Axis::from_str)
+            .or_else(|| profile.axis.as_deref().and_then(Axis::from_str))
+            .or_else(|| file.axis.as_deref().and_then(Axis::from_str))
+            .unwrap_or_default();
+
+        let blend = cli.blend.as_deref()
+            .and_then(Blend::from_str)
+            .or_else(|| profile.blend.as_deref().and_then(Blend::from_str))
+            .or_else(|| file.blend.as_deref().and_then(Blend::from_str))
+            .unwrap_or_default();
+
+        // The GL backend draws shapes in a shader, so textures and chains stay on GSK
+        let mut backend = cli.backend.as_deref()
+            .and_then(Backend::from_str)
+            .or_else(|| profile.backend.as_deref().and_then(Backend::from_str))
+            .or_else(|| file.backend.as_deref().and_then(Backend::from_str))
+            .unwrap_or_default();
+        let gl_unsupported = |sh: Shape| matches!(sh, Shape::Glyph | Shape::Sprite | Shape::Streamer);
+        if backend == Backend::Gl
+            && if shapes.is_empty() { gl_unsupported(shape) } else { shapes.iter().any(|m| gl_unsupported(m.shape)) }
+        {
+            eprintln!("gl backend can't draw glyph, sprite or streamer shapes; using gsk");
+            backend = Backend::Gsk;
+        }
+
+        let form = cli.form.as_deref()
+            .and_then(Form::from_str)
+            .or_else(|| profile.form.as_deref().and_then(Form::from_str))
+            .or_else(|| file.form.as_deref().and_then(Form::from_str))
+            .unwrap_or_default();
+
+        // Gradients skip the palette fallback: no valid stops means no gradient
+        let gradient: Vec<[f32; 3]> = cli.gradient.as_ref()
+            .or(profile.gradient.as_ref())
+            .or(file.gradient.as_ref())
+            .map(|g| g.iter().filter_map(|c| parse_hex_color(c.trim())).collect())
+            .unwrap_or_default();
+
+        let particles = pick!(cli.particles, profile.particles, file.particles, dp);
+        let duration = pick!(cli.duration, profile.duration, file.duration, dd);
+        // A negative cone has no angles to pick from and a rate of 0 never emits
+        let cone = cli.cone.or(profile.cone).or(file.cone)
+            .filter(|c| *c >= 0.0)
+            .unwrap_or(30.0);
+        let rate = cli.rate.or(profile.rate).or(file.rate)
+            .filter(|r| *r > 0.0)
+            .unwrap_or(particles as f64 / duration);
+
         Self {
-            anim_type, shape,
-            particles: pick!(cli.particles, profile.particles, file.particles, dp),
-            duration:  pick!(cli.duration,  profile.duration,  file.duration,  dd),
+            anim_type, shape, shapes, particles, duration,
             gravity:   pick!(cli.gravity,   profile.gravity,   file.gravity,   dg),
             drag:      pick!(cli.drag,      profile.drag,      file.drag,      ddr),
             speed_min: pick!(cli.speed_min, profile.speed_min, file.speed_min, dsn),
             speed_max: pick!(cli.speed_max, profile.speed_max, file.speed_max, dsx),
             spread:    pick!(cli.spread,    profile.spread,    file.spread,    dsp),
             fade:      pick!(cli.fade,      profile.fade,      file.fade,      df),
+            trail:     pick!(cli.trail,     profile.trail,     file.trail,     anim_type.default_trail()),
+            glow:      pick!(cli.glow,      profile.glow,      file.glow,      0.0),
+            blend,
+            monitor: cli.monitor.as_deref()
+                .and_then(Output::from_str)
+                .or_else(|| profile.monitor.as_deref().and_then(Output::from_str))
+                .or_else(|| file.monitor.as_deref().and_then(Output::from_str))
+                .unwrap_or_default(),
+            span:      pick!(cli.span,      profile.span,      file.span,      false),
+            region: cli.region.as_deref()
+                .and_then(Region::from_str)
+                .or_else(|| profile.region.as_deref().and_then(Region::from_str))
+                .or_else(|| file.region.as_deref().and_then(Region::from_str)),
+            backend,
+            stats: cli.stats,
+            // Particles store their layer index in a byte
+            layers:    pick!(cli.layers,    profile.layers,    file.layers,    1).clamp(1, 255),
+            depth,
+            depth_blur: pick!(cli.depth_blur, profile.depth_blur, file.depth_blur, 0.0),
+            origin,
+            angle:     pick!(cli.angle,     profile.angle,     file.angle,     0.0),
+            cone, rate,
+            spin:      pick!(cli.spin,      profile.spin,      file.spin,      3.0),
+            pull:      pick!(cli.pull,      profile.pull,      file.pull,      150.0),
+            axis,
+            // Above 1 coins would gain speed on every hit, below 0 sink through the floor
+            bounce:    pick!(cli.bounce,    profile.bounce,    file.bounce,    0.45).clamp(0.0, 1.0),
+            stars:     pick!(cli.stars,     profile.stars,     file.stars,     0),
+            text: cli.text.or(profile.text).or(file.text),
+            image: cli.image.or(profile.image).or(file.image).map(|p| expand_home(&p)),
+            form,
+            glyphs: cli.glyphs.or(profile.glyphs).or(file.glyphs)
+                .filter(|g| !g.is_empty())
+                .unwrap_or_else(|| DEFAULT_GLYPHS.iter().map(|g| g.to_string()).collect()),
+            sprites,
+            tint:      pick!(cli.tint,      profile.tint,      file.tint,      false),
             colors,
+            gradient,
+            rainbow: cli.rainbow.as_deref()
+                .and_then(Rainbow::from_str)
+                .or_else(|| profile.rainbow.as_deref().and_then(Rainbow::from_str))
+                .or_else(|| file.rainbow.as_deref().and_then(Rainbow::from_str)),
+            hue_speed: pick!(cli.hue_speed, profile.hue_speed, file.hue_speed, 120.0),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span_leaves_the_next_word_to_the_profile() {
+        let cli = Cli::try_parse_from(["confet", "--span", "fireworks"]).unwrap();
+        assert_eq!(cli.span, Some(true));
+        assert_eq!(cli.profile.as_deref(), Some("fireworks"));
+
+        let cli = Cli::try_parse_from(["confet", "--monitor", "all", "--span", "snow"]).unwrap();
+        assert_eq!(cli.span, Some(true));
+        assert_eq!(cli.profile.as_deref(), Some("snow"));
+
+        let cli = Cli::try_parse_from(["confet", "fireworks", "--span=false"]).unwrap();
+        assert_eq!(cli.span, Some(false));
+        assert_eq!(Cli::try_parse_from(["confet"]).unwrap().span, None);
+    }
+
+    #[test]
+    fn tint_leaves_the_next_word_to_the_profile() {
+        let cli = Cli::try_parse_from(["confet", "--tint", "ducks"]).unwrap();
+        assert_eq!(cli.tint, Some(true));
+        assert_eq!(cli.profile.as_deref(), Some("ducks"));
+        assert_eq!(Cli::try_parse_from(["confet", "--tint=false"]).unwrap().tint, Some(false));
+    }
+}
@@ -0,0 +1,333 @@
+use crate::config::settings;
+use crate::particle::{self, Particles};
+use crate::render::Renderer;
+use crate::shapes::Seg;
+use crate::types::{Blend, Shape};
+use std::ffi::{c_void, CStr, CString};
+
+// Shape ids understood by the fragment shader
+pub const RECT: f32 = 0.0;
+pub const CIRCLE: f32 = 1.0;
+pub const STAR: f32 = 2.0;
+pub const HEART: f32 = 3.0;
+pub const TRIANGLE: f32 = 4.0;
+pub const DIAMOND: f32 = 5.0;
+/// Radial falloff behind a particle
+pub const GLOW: f32 = 6.0;
+/// Streak fading from the quad's left edge to its right
+pub const STREAK: f32 = 7.0;
+
+/// Floats per instance: center x/y, width, height, rotation, shape id, r, g, b, alpha
+pub const STRIDE: usize = 10;
+
+pub fn shape_id(shape: Shape) -> f32 {
+    match shape {
+        Shape::Circle => CIRCLE,
+        Shape::Star => STAR,
+        Shape::Heart => HEART,
+        Shape::Triangle => TRIANGLE,
+        Shape::Diamond => DIAMOND,
+        _ => RECT,
+    }
+}
+
+const VERTEX: &str = r#"
+layout(location = 0) in vec2 corner;
+layout(location = 1) in vec4 geom;
+layout(location = 2) in vec2 spin;
+layout(location = 3) in vec4 color;
+uniform vec4 view;
+out vec2 uv;
+out vec4 col;
+flat out int shape;
+
+void main() {
+    float c = cos(spin.x), s = sin(spin.x);
+    vec2 p = corner * geom.zw;
+    p = vec2(p.x * c - p.y * s, p.x * s + p.y * c) + geom.xy;
+    p = (p - view.xy) / view.zw;
+    gl_Position = vec4(p.x * 2.0 - 1.0, 1.0 - p.y * 2.0, 0.0, 1.0);
+    uv = corner;
+    col = color;
+    shape = int(spin.y + 0.5);
+}
+"#;
+
+// Distance functions are in the same unit box as shapes::outline, negative inside
+const FRAGMENT: &str = r#"
+in vec2 uv;
+in vec4 col;
+flat in int shape;
+out vec4 frag;
+
+float dot2(vec2 v) { return dot(v, v); }
+
+float star(vec2 p) {
+    const vec2 k1 = vec2(0.809016994, -0.587785252);
+    const vec2 k2 = vec2(-k1.x, k1.y);
+    p = vec2(abs(p.x), -p.y);
+    p -= 2.0 * max(dot(k1, p), 0.0) * k1;
+    p -= 2.0 * max(dot(k2, p), 0.0) * k2;
+    p.x = abs(p.x);
+    p.y -= 0.5;
+    vec2 ba = 0.4 * vec2(-k1.y, k1.x) - vec2(0.0, 1.0);
+    float h = clamp(dot(p, ba) / dot(ba, ba), 0.0, 0.5);
+    return length(p - ba * h) * sign(p.y * ba.x - p.x * ba.y);
+}
+
+float heart(vec2 p) {
+    p = vec2(abs(p.x), 0.45 - p.y) * 1.15;
+    if (p.x + p.y > 1.0) return (sqrt(dot2(p - vec2(0.25, 0.75))) - 0.353553) / 1.15;
+    return sqrt(min(dot2(p - vec2(0.0, 1.0)), dot2(p - 0.5 * max(p.x + p.y, 0.0)))) * sign(p.x - p.y) / 1.15;
+}
+
+float triangle(vec2 p) {
+    vec2 p0 = vec2(0.0, -0.5), p1 = vec2(0.5, 0.4), p2 = vec2(-0.5, 0.4);
+    vec2 e0 = p1 - p0, e1 = p2 - p1, e2 = p0 - p2;
+    vec2 v0 = p - p0, v1 = p - p1, v2 = p - p2;
+    vec2 q0 = v0 - e0 * clamp(dot(v0, e0) / dot(e0, e0), 0.0, 1.0);
+    vec2 q1 = v1 - e1 * clamp(dot(v1, e1) / dot(e1, e1), 0.0, 1.0);
+    vec2 q2 = v2 - e2 * clamp(dot(v2, e2) / dot(e2, e2), 0.0, 1.0);
+    float s = sign(e0.x * e2.y - e0.y * e2.x);
+    vec2 d = min(min(vec2(dot(q0, q0), s * (v0.x * e0.y - v0.y * e0.x)),
+                     vec2(dot(q1, q1), s * (v1.x * e1.y - v1.y * e1.x))),
+                     vec2(dot(q2, q2), s * (v2.x * e2.y - v2.y * e2.x)));
+    return -sqrt(d.x) * sign(d.y);
+}
+
+float diamond(vec2 p) {
+    vec2 b = vec2(0.35, 0.5);
+    p = abs(p);
+    float h = clamp(((b.x - 2.0 * p.x) * b.x - (b.y - 2.0 * p.y) * b.y) / dot(b, b), -1.0, 1.0);
+    float d = length(p - 0.5 * b * vec2(1.0 - h, 1.0 + h));
+    return d * sign(p.x * b.y + p.y * b.x - b.x * b.y);
+}
+
+void main() {
+    float a;
+    if (shape == 6) {
+        a = max(1.0 - length(uv) * 2.0, 0.0) * 0.45;
+    } else if (shape == 7) {
+        a = (uv.x + 0.5) * 0.6 * (1.0 - smoothstep(0.15, 0.5, abs(uv.y)));
+    } else if (shape == 0) {
+        a = 1.0;
+    } else {
+        float d = shape == 1 ? length(uv) - 0.5
+                : shape == 2 ? star(uv)
+                : shape == 3 ? heart(uv)
+                : shape == 4 ? triangle(uv)
+                : diamond(uv);
+        a = clamp(0.5 - d / max(fwidth(d), 1e-4), 0.0, 1.0);
+    }
+    a *= col.a;
+    frag = vec4(col.rgb * a, a);
+}
+"#;
+
+/// Collects draw commands as instance records, tracking the transform stack so particles
+/// lay out exactly as on the other backends. Quads can only rotate and stretch along their
+/// own axes, which covers every transform `Particles::render` makes. Glyphs, sprites and
+/// streamer paths never reach this backend.
+pub struct InstanceRenderer<'a> {
+    out: &'a mut Vec<f32>,
+    /// Affine transform `[a, b, c, d, e, f]`: x' = a x + c y + e, y' = b x + d y + f
+    m: [f32; 6],
+    stack: Vec<[f32; 6]>,
+}
+
+impl<'a> InstanceRenderer<'a> {
+    pub fn new(out: &'a mut Vec<f32>) -> Self {
+        Self { out, m: [1.0, 0.0, 0.0, 1.0, 0.0, 0.0], stack: Vec::new() }
+    }
+
+    /// Pushes a `w` x `h` quad centered `dx` along the local x axis.
+    fn quad(&mut self, dx: f32, w: f32, h: f32, id: f32, c: &[f32; 3], alpha: f32) {
+        let [a, b, cc, d, e, f] = self.m;
+        let (cx, cy) = (a * dx + e, b * dx + f);
+        let (sx, sy) = (a.hypot(b), cc.hypot(d));
+        self.out.extend_from_slice(&[cx, cy, w * sx, h * sy, b.atan2(a), id, c[0], c[1], c[2], alpha]);
+    }
+}
+
+impl Renderer for InstanceRenderer<'_> {
+    fn save(&mut self) { self.stack.push(self.m); }
+    fn restore(&mut self) { if let Some(m) = self.stack.pop() { self.m = m; } }
+
+    fn translate(&mut self, x: f32, y: f32) {
+        let [a, b, c, d, e, f] = self.m;
+        self.m = [a, b, c, d, a * x + c * y + e, b * x + d * y + f];
+    }
+
+    fn rotate(&mut self, radians: f32) {
+        let (sin, cos) = radians.sin_cos();
+        let [a, b, c, d, e, f] = self.m;
+        self.m = [a * cos + c * sin, b * cos + d * sin, c * cos - a * sin, d * cos - b * sin, e, f];
+    }
+
+    fn scale(&mut self, sx: f32, sy: f32) {
+        let [a, b, c, d, e, f] = self.m;
+        self.m = [a * sx, b * sx, c * sy, d * sy, e, f];
+    }
+
+    fn shape(&mut self, shape: Shape, w: f32, h: f32, c: &[f32; 3], alpha: f32) {
+        self.quad(0.0, w, h, shape_id(shape), c, alpha);
+    }
+
+    fn path(&mut self, _segs: &[Seg], _c: &[f32; 3], _alpha: f32) {}
+    fn glyph(&mut self, _idx: u32, _w: f32, _h: f32, _c: &[f32; 3], _alpha: f32) {}
+    fn sprite(&mut self, _idx: u32, _h: f32, _flip: f32, _c: &[f32; 3], _alpha: f32) {}
+
+    fn glow(&mut self, c: &[f32; 3], alpha: f32, r: f32) {
+        self.quad(0.0, r * 2.0, r * 2.0, GLOW, c, alpha);
+    }
+
+    fn streak(&mut self, c: &[f32; 3], alpha: f32, len: f32, width: f32) {
+        self.quad(-len / 2.0, len, width, STREAK, c, alpha);
+    }
+
+    fn coin(&mut self, w: f32, h: f32, c: &[f32; 3], alpha: f32, shade: f32, _highlight: f32) {
+        // A flat disc darkened as it turns edge-on; the highlight band is left to gsk
+        self.quad(0.0, w, h, CIRCLE, &c.map(|v| v * shade), alpha);
+    }
+}
+
+/// Loads GL entry points through libepoxy, which GTK already uses: its exported
+/// `epoxy_glFoo` symbols point at stubs that dispatch to the current context's driver.
+fn load() {
+    #[cfg(target_os = "macos")]
+    const EPOXY: &CStr = c"libepoxy.0.dylib";
+    #[cfg(not(target_os = "macos"))]
+    const EPOXY: &CStr = c"libepoxy.so.0";
+
+    let lib = unsafe { libc::dlopen(EPOXY.as_ptr(), libc::RTLD_LAZY) };
+    if lib.is_null() {
+        eprintln!("gl backend: failed to load {}", EPOXY.to_string_lossy());
+        std::process::exit(1);
+    }
+    gl::load_with(|name| {
+        let sym = CString::new(format!("epoxy_{name}")).unwrap();
+        let ptr = unsafe { libc::dlsym(lib, sym.as_ptr()) } as *const *const c_void;
+        if ptr.is_null() { std::ptr::null() } else { unsafe { *ptr } }
+    });
+}
+
+fn compile(kind: gl::types::GLenum, header: &str, src: &str) -> gl::types::GLuint {
+    let text = CString::new(format!("{header}{src}")).unwrap();
+    unsafe {
+        let shader = gl::CreateShader(kind);
+        gl::ShaderSource(shader, 1, &text.as_ptr(), std::ptr::null());
+        gl::CompileShader(shader);
+        let mut ok = 0;
+        gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut ok);
+        if ok == 0 {
+            let mut log = vec![0u8; 4096];
+            let mut len = 0;
+            gl::GetShaderInfoLog(shader, log.len() as i32, &mut len, log.as_mut_ptr().cast());
+            eprintln!("gl backend: shader failed to compile: {}", String::from_utf8_lossy(&log[..len as usize]));
+            std::process::exit(1);
+        }
+        shader
+    }
+}
+
+/// Draws every particle as an instanced quad in a single call, uploading the instance
+/// records once per frame. Must be created and used with the GL area's context current.
+pub struct Instanced {
+    program: gl::types::GLuint,
+    vao: gl::types::GLuint,
+    quad: gl::types::GLuint,
+    buffer: gl::types::GLuint,
+    view: gl::types::GLint,
+    data: Vec<f32>,
+}
+
+impl Instanced {
+    /// `es` selects GLSL ES 3.0 over desktop GLSL 3.30; both run on Mesa llvmpipe.
+    pub fn new(es: bool) -> Self {
+        load();
+        let header = if es { "#version 300 es\nprecision highp float;\n" } else { "#version 330 core\n" };
+        unsafe {
+            let vs = compile(gl::VERTEX_SHADER, header, VERTEX);
+            let fs = compile(gl::FRAGMENT_SHADER, header, FRAGMENT);
+            let program = gl::CreateProgram();
+            gl::AttachShader(program, vs);
+            gl::AttachShader(program, fs);
+            gl::LinkProgram(program);
+            gl::DeleteShader(vs);
+            gl::DeleteShader(fs);
+            let mut ok = 0;
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut ok);
+            if ok == 0 {
+                eprintln!("gl backend: shader program failed to link");
+                std::process::exit(1);
+            }
+            let view = gl::GetUniformLocation(program, c"view".as_ptr());
+
+            let (mut vao, mut quad, mut buffer) = (0, 0, 0);
+            gl::GenVertexArrays(1, &mut vao);
+            gl::GenBuffers(1, &mut quad);
+            gl::GenBuffers(1, &mut buffer);
+            gl::BindVertexArray(vao);
+
+            let corners: [f32; 8] = [-0.5, -0.5, 0.5, -0.5, -0.5, 0.5, 0.5, 0.5];
+            gl::BindBuffer(gl::ARRAY_BUFFER, quad);
+            gl::BufferData(gl::ARRAY_BUFFER, size_of_val(&corners) as isize, corners.as_ptr().cast(), gl::STATIC_DRAW);
+            gl::EnableVertexAttribArray(0);
+            gl::VertexAttribPointer(0, 2, gl::FLOAT, gl::FALSE, 0, std::ptr::null());
+
+            gl::BindBuffer(gl::ARRAY_BUFFER, buffer);
+            let stride = (STRIDE * size_of::<f32>()) as i32;
+            for (loc, size, offset) in [(1, 4, 0), (2, 2, 4), (3, 4, 6)] {
+                gl::EnableVertexAttribArray(loc);
+                gl::VertexAttribPointer(loc, size, gl::FLOAT, gl::FALSE, stride, (offset * size_of::<f32>()) as *const c_void);
+                gl::VertexAttribDivisor(loc, 1);
+            }
+            gl::BindVertexArray(0);
+
+            Self { program, vao, quad, buffer, view, data: Vec::new() }
+        }
+    }
+
+    /// Clears the area and draws the particles' current instance records, showing the
+    /// `view` slice of the simulation.
+    pub fn render(&mut self, ps: &Particles, alpha: f32, t: f64, view: &graphene::Rect) {
+        self.data.clear();
+        let mut r = InstanceRenderer::new(&mut self.data);
+        // Farthest layer first; depth blur is left to gsk
+        for k in (0..settings().layers).rev() {
+            ps.render(&mut r, alpha * particle::layer_fade(k), t, k as u8);
+        }
+        let count = (self.data.len() / STRIDE) as i32;
+        unsafe {
+            gl::ClearColor(0.0, 0.0, 0.0, 0.0);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            if count == 0 { return; }
+            gl::Enable(gl::BLEND);
+            // The shader writes premultiplied color
+            match settings().blend {
+                Blend::Normal => gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_ALPHA),
+                Blend::Additive => gl::BlendFunc(gl::ONE, gl::ONE),
+                Blend::Screen => gl::BlendFunc(gl::ONE, gl::ONE_MINUS_SRC_COLOR),
+            }
+            gl::UseProgram(self.program);
+            gl::Uniform4f(self.view, view.x(), view.y(), view.width(), view.height());
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.buffer);
+            gl::BufferData(gl::ARRAY_BUFFER, size_of_val(self.data.as_slice()) as isize, self.data.as_ptr().cast(), gl::STREAM_DRAW);
+            gl::DrawArraysInstanced(gl::TRIANGLE_STRIP, 0, 4, count);
+            gl::BindVertexArray(0);
+            gl::UseProgram(0);
+        }
+    }
+}
+
+impl Drop for Instanced {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.buffer);
+            gl::DeleteBuffers(1, &self.quad);
+            gl::DeleteVertexArrays(1, &self.vao);
+            gl::DeleteProgram(self.program);
+        }
+    }
+}
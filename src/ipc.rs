@@ -0,0 +1,350 @@
+//! Compositor queries over the Sway and Hyprland IPC sockets. Both are found through
+//! environment variables, so pointing `SWAYSOCK` or `HYPRLAND_INSTANCE_SIGNATURE` (with
+//! `XDG_RUNTIME_DIR`) at a fake socket serving canned JSON exercises everything here.
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Sway `GET_OUTPUTS` message type
+const SWAY_GET_OUTPUTS: u32 = 3;
+/// Sway `GET_TREE` message type
+const SWAY_GET_TREE: u32 = 4;
+
+#[derive(Deserialize)]
+struct SwayOutput {
+    name: String,
+    #[serde(default)]
+    focused: bool,
+}
+
+/// Node of Sway's layout tree: the root, outputs, workspaces and containers
+#[derive(Deserialize)]
+struct SwayNode {
+    #[serde(rename = "type", default)]
+    kind: String,
+    #[serde(default)]
+    focused: bool,
+    rect: Option<SwayRect>,
+    #[serde(default)]
+    nodes: Vec<SwayNode>,
+    #[serde(default)]
+    floating_nodes: Vec<SwayNode>,
+}
+
+#[derive(Deserialize)]
+struct SwayRect {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Deserialize)]
+struct HyprMonitor {
+    name: String,
+    #[serde(default)]
+    focused: bool,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    scale: f64,
+}
+
+#[derive(Deserialize)]
+struct HyprCursor {
+    x: f64,
+    y: f64,
+}
+
+/// `activewindow` reply; Hyprland answers `{}` when nothing has focus
+#[derive(Deserialize)]
+struct HyprWindow {
+    at: [f64; 2],
+    size: [f64; 2],
+}
+
+/// Sends an i3-ipc message to Sway and parses the reply.
+fn sway<T: DeserializeOwned>(kind: u32, payload: &str) -> Option<T> {
+    let mut sock = UnixStream::connect(std::env::var_os("SWAYSOCK")?).ok()?;
+    sock.set_read_timeout(Some(TIMEOUT)).ok()?;
+    let mut msg = b"i3-ipc".to_vec();
+    msg.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    msg.extend_from_slice(&kind.to_ne_bytes());
+    msg.extend_from_slice(payload.as_bytes());
+    sock.write_all(&msg).ok()?;
+
+    // Reply header: magic, payload length, message type
+    let mut header = [0u8; 14];
+    sock.read_exact(&mut header).ok()?;
+    if &header[..6] != b"i3-ipc" { return None; }
+    let len = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+    let mut body = vec![0u8; len];
+    sock.read_exact(&mut body).ok()?;
+    serde_json::from_slice(&body).ok()
+}
+
+fn hyprland_socket() -> Option<PathBuf> {
+    let sig = std::env::var_os("HYPRLAND_INSTANCE_SIGNATURE")?;
+    // Hyprland 0.40 moved its sockets from /tmp/hypr to the runtime dir
+    let runtime = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("hypr").join(&sig).join(".socket.sock"));
+    let legacy = PathBuf::from("/tmp/hypr").join(&sig).join(".socket.sock");
+    runtime.filter(|p| p.exists()).or(Some(legacy))
+}
+
+/// Sends a `j/`-prefixed (JSON) command to Hyprland and parses the reply.
+fn hyprland<T: DeserializeOwned>(command: &str) -> Option<T> {
+    let mut sock = UnixStream::connect(hyprland_socket()?).ok()?;
+    sock.set_read_timeout(Some(TIMEOUT)).ok()?;
+    sock.write_all(format!("j/{command}").as_bytes()).ok()?;
+    let mut reply = Vec::new();
+    sock.read_to_end(&mut reply).ok()?;
+    serde_json::from_slice(&reply).ok()
+}
+
+/// Connector name of the output holding keyboard focus.
+pub fn focused_output() -> Option<String> {
+    if let Some(outputs) = sway::<Vec<SwayOutput>>(SWAY_GET_OUTPUTS, "") {
+        return outputs.into_iter().find(|o| o.focused).map(|o| o.name);
+    }
+    let monitors: Vec<HyprMonitor> = hyprland("monitors")?;
+    monitors.into_iter().find(|m| m.focused).map(|m| m.name)
+}
+
+/// Left, top, width and height of the focused window in the desktop layout.
+pub fn focused_window() -> Option<[f64; 4]> {
+    if let Some(tree) = sway::<SwayNode>(SWAY_GET_TREE, "") {
+        let r = focused_node(&tree)?.rect.as_ref()?;
+        return Some([r.x, r.y, r.width, r.height]);
+    }
+    let win: HyprWindow = hyprland("activewindow")?;
+    Some([win.at[0], win.at[1], win.size[0], win.size[1]])
+}
+
+/// Focused window in a Sway tree. Workspaces and outputs also take focus when empty,
+/// so only containers count.
+fn focused_node(node: &SwayNode) -> Option<&SwayNode> {
+    if node.focused {
+        return matches!(node.kind.as_str(), "con" | "floating_con").then_some(node);
+    }
+    node.nodes.iter().chain(&node.floating_nodes).find_map(focused_node)
+}
+
+/// Pointer position in the desktop layout. Only Hyprland exposes it.
+pub fn cursor_position() -> Option<(f64, f64)> {
+    let pos: HyprCursor = hyprland("cursorpos")?;
+    Some((pos.x, pos.y))
+}
+
+/// Connector name of the output under the pointer. Sway doesn't expose the pointer
+/// position, so there this is the focused output, which follows the pointer by default.
+pub fn pointer_output() -> Option<String> {
+    let Some((x, y)) = cursor_position() else { return focused_output() };
+    let monitors: Vec<HyprMonitor> = hyprland("monitors")?;
+    monitors.into_iter()
+        .find(|m| {
+            // Positions are in layout space but sizes in physical pixels
+            let scale = m.scale.max(0.1);
+            x >= m.x && x < m.x + m.width / scale && y >= m.y && y < m.y + m.height / scale
+        })
+        .map(|m| m.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::os::unix::net::UnixListener;
+    use std::path::Path;
+    use std::sync::Mutex;
+
+    /// Tests point the process-wide socket variables at their own servers, one at a time
+    static ENV: Mutex<()> = Mutex::new(());
+
+    /// Directory holding a test's sockets, removed when the test is done with it
+    struct ScratchDir(PathBuf);
+
+    impl ScratchDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("confet-ipc-{}-{name}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Serves canned i3-ipc replies by message type until the test process exits.
+    fn fake_sway(path: &Path, replies: HashMap<u32, &'static str>) {
+        let listener = UnixListener::bind(path).unwrap();
+        std::thread::spawn(move || {
+            for mut conn in listener.incoming().flatten() {
+                let mut header = [0u8; 14];
+                if conn.read_exact(&mut header).is_err() { continue; }
+                let len = u32::from_ne_bytes(header[6..10].try_into().unwrap()) as usize;
+                let kind = u32::from_ne_bytes(header[10..14].try_into().unwrap());
+                let _ = conn.read_exact(&mut vec![0u8; len]);
+                let body = replies.get(&kind).copied().unwrap_or("{}");
+                let mut msg = b"i3-ipc".to_vec();
+                msg.extend_from_slice(&(body.len() as u32).to_ne_bytes());
+                msg.extend_from_slice(&kind.to_ne_bytes());
+                msg.extend_from_slice(body.as_bytes());
+                let _ = conn.write_all(&msg);
+            }
+        });
+    }
+
+    /// Serves canned replies by `j/` command until the test process exits.
+    fn fake_hyprland(path: &Path, replies: HashMap<&'static str, &'static str>) {
+        let listener = UnixListener::bind(path).unwrap();
+        std::thread::spawn(move || {
+            for mut conn in listener.incoming().flatten() {
+                let mut buf = [0u8; 256];
+                let n = conn.read(&mut buf).unwrap_or(0);
+                let command = std::str::from_utf8(&buf[..n]).unwrap_or("");
+                let body = replies.get(command).copied().unwrap_or("{}");
+                let _ = conn.write_all(body.as_bytes());
+            }
+        });
+    }
+
+    fn with_sway(name: &str, replies: HashMap<u32, &'static str>, test: impl FnOnce()) {
+        let _guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = ScratchDir::new(name);
+        let sock = dir.0.join("sway.sock");
+        fake_sway(&sock, replies);
+        std::env::set_var("SWAYSOCK", &sock);
+        std::env::remove_var("HYPRLAND_INSTANCE_SIGNATURE");
+        test();
+    }
+
+    fn with_hyprland(name: &str, replies: HashMap<&'static str, &'static str>, test: impl FnOnce()) {
+        let _guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = ScratchDir::new(name);
+        let sock_dir = dir.0.join("hypr").join("test");
+        std::fs::create_dir_all(&sock_dir).unwrap();
+        fake_hyprland(&sock_dir.join(".socket.sock"), replies);
+        std::env::remove_var("SWAYSOCK");
+        std::env::set_var("XDG_RUNTIME_DIR", &dir.0);
+        std::env::set_var("HYPRLAND_INSTANCE_SIGNATURE", "test");
+        test();
+    }
+
+    const SWAY_OUTPUTS: &str = r#"[
+        {"name": "DP-1", "focused": false, "rect": {"x": 0, "y": 0, "width": 2560, "height": 1440}},
+        {"name": "DP-2", "focused": true, "rect": {"x": 2560, "y": 0, "width": 2560, "height": 1440}}
+    ]"#;
+
+    // DP-2 is 3840 physical pixels wide at scale 1.5, so it spans x 2560..5120 in the layout
+    const HYPR_MONITORS: &str = r#"[
+        {"name": "DP-1", "focused": false, "x": 0, "y": 0, "width": 2560, "height": 1440, "scale": 1.0},
+        {"name": "DP-2", "focused": true, "x": 2560, "y": 0, "width": 3840, "height": 2160, "scale": 1.5}
+    ]"#;
+
+    #[test]
+    fn sway_focused_output() {
+        with_sway("sway-focused", HashMap::from([(SWAY_GET_OUTPUTS, SWAY_OUTPUTS)]), || {
+            assert_eq!(focused_output().as_deref(), Some("DP-2"));
+        });
+    }
+
+    #[test]
+    fn sway_pointer_output_falls_back_to_focused() {
+        with_sway("sway-pointer", HashMap::from([(SWAY_GET_OUTPUTS, SWAY_OUTPUTS)]), || {
+            assert_eq!(pointer_output().as_deref(), Some("DP-2"));
+        });
+    }
+
+    #[test]
+    fn hyprland_focused_output() {
+        with_hyprland("hypr-focused", HashMap::from([("j/monitors", HYPR_MONITORS)]), || {
+            assert_eq!(focused_output().as_deref(), Some("DP-2"));
+        });
+    }
+
+    #[test]
+    fn hyprland_pointer_output() {
+        let replies = HashMap::from([("j/monitors", HYPR_MONITORS), ("j/cursorpos", r#"{"x": 1200, "y": 700}"#)]);
+        with_hyprland("hypr-pointer", replies, || {
+            assert_eq!(pointer_output().as_deref(), Some("DP-1"));
+        });
+    }
+
+    #[test]
+    fn hyprland_pointer_output_divides_by_scale() {
+        let inside = HashMap::from([("j/monitors", HYPR_MONITORS), ("j/cursorpos", r#"{"x": 5000, "y": 100}"#)]);
+        with_hyprland("hypr-scale-inside", inside, || {
+            assert_eq!(pointer_output().as_deref(), Some("DP-2"));
+        });
+        // Inside DP-2's physical width, but past its logical one
+        let outside = HashMap::from([("j/monitors", HYPR_MONITORS), ("j/cursorpos", r#"{"x": 6000, "y": 100}"#)]);
+        with_hyprland("hypr-scale-outside", outside, || {
+            assert_eq!(pointer_output(), None);
+        });
+    }
+
+    const SWAY_TREE: &str = r#"{"type": "root", "focused": false, "nodes": [
+        {"type": "output", "name": "DP-1", "focused": false, "nodes": [
+            {"type": "workspace", "name": "1", "focused": false, "nodes": [
+                {"type": "con", "focused": false, "rect": {"x": 0, "y": 0, "width": 1280, "height": 1440}}
+            ], "floating_nodes": [
+                {"type": "floating_con", "focused": true, "rect": {"x": 400, "y": 300, "width": 800, "height": 600}}
+            ]}
+        ]}
+    ]}"#;
+
+    // Switching to an empty workspace gives the workspace itself focus
+    const SWAY_TREE_EMPTY: &str = r#"{"type": "root", "focused": false, "nodes": [
+        {"type": "output", "name": "DP-1", "focused": false, "nodes": [
+            {"type": "workspace", "name": "2", "focused": true, "rect": {"x": 0, "y": 0, "width": 2560, "height": 1440}, "nodes": []}
+        ]}
+    ]}"#;
+
+    #[test]
+    fn sway_focused_window() {
+        with_sway("sway-window", HashMap::from([(SWAY_GET_TREE, SWAY_TREE)]), || {
+            assert_eq!(focused_window(), Some([400.0, 300.0, 800.0, 600.0]));
+        });
+    }
+
+    #[test]
+    fn sway_focused_window_skips_empty_workspace() {
+        with_sway("sway-window-empty", HashMap::from([(SWAY_GET_TREE, SWAY_TREE_EMPTY)]), || {
+            assert_eq!(focused_window(), None);
+        });
+    }
+
+    #[test]
+    fn hyprland_focused_window() {
+        let replies = HashMap::from([("j/activewindow", r#"{"class": "kitty", "at": [2600, 40], "size": [1200, 800]}"#)]);
+        with_hyprland("hypr-window", replies, || {
+            assert_eq!(focused_window(), Some([2600.0, 40.0, 1200.0, 800.0]));
+        });
+        with_hyprland("hypr-window-none", HashMap::from([("j/activewindow", "{}")]), || {
+            assert_eq!(focused_window(), None);
+        });
+    }
+
+    #[test]
+    fn no_compositor() {
+        let _guard = ENV.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::remove_var("SWAYSOCK");
+        std::env::remove_var("HYPRLAND_INSTANCE_SIGNATURE");
+        assert_eq!(focused_output(), None);
+        assert_eq!(pointer_output(), None);
+        assert_eq!(cursor_position(), None);
+        assert_eq!(focused_window(), None);
+    }
+}
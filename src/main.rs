@@ -1,11 +1,17 @@
 mod canvas;
 mod config;
+mod instanced;
+mod ipc;
+mod monitors;
 mod particle;
 mod platform;
+mod render;
+mod shapes;
+mod targets;
 mod types;
 
 use canvas::Canvas;
-use config::Settings;
+use config::{settings, Settings};
 use gtk4::prelude::*;
 use gtk4::gdk;
 
@@ -24,30 +30,50 @@ fn main() {
         .build();
 
     app.connect_activate(|app| {
-        let win = gtk4::Window::builder().application(app).build();
-
-        platform::setup_window(&win);
-
+        let display = gdk::Display::default().unwrap();
         let css = gtk4::CssProvider::new();
         css.load_from_data(
             "window.background, window.background * { background: unset; background-color: rgba(0,0,0,0); }",
         );
-        gtk4::style_context_add_provider_for_display(
-            &gdk::Display::default().unwrap(), &css, gtk4::STYLE_PROVIDER_PRIORITY_USER,
-        );
+        gtk4::style_context_add_provider_for_display(&display, &css, gtk4::STYLE_PROVIDER_PRIORITY_USER);
+        platform::init(&display);
 
-        let canvas = Canvas::new();
-        canvas.set_hexpand(true);
-        canvas.set_vexpand(true);
-        win.set_child(Some(&canvas));
+        // One window per selected monitor, each bound to its output
+        let mut windows = Vec::new();
+        for monitor in monitors::select(&display) {
+            let Some(area) = monitors::area(&monitor) else { continue };
+            let win = gtk4::Window::builder().application(app).build();
 
-        win.present();
+            platform::setup_window(&win, &monitor, &area);
 
-        let display = gdk::Display::default().unwrap();
-        let monitor: gdk::Monitor = display.monitors()
-            .item(0).unwrap().downcast().unwrap();
-        let geom = monitor.geometry();
-        canvas.start(geom.width() as f64, geom.height() as f64);
+            let canvas = Canvas::new();
+            canvas.set_hexpand(true);
+            canvas.set_vexpand(true);
+            win.set_child(Some(&canvas));
+
+            win.present();
+            windows.push((canvas, area));
+        }
+        if windows.is_empty() {
+            eprintln!("the focused window isn't on a covered monitor; try --monitor focused");
+            std::process::exit(1);
+        }
+
+        if settings().span && windows.len() > 1 {
+            // One simulation over the bounding box of every covered area of the desktop layout
+            let x0 = windows.iter().map(|(_, g)| g.x()).min().unwrap();
+            let y0 = windows.iter().map(|(_, g)| g.y()).min().unwrap();
+            let x1 = windows.iter().map(|(_, g)| g.x() + g.width()).max().unwrap();
+            let y1 = windows.iter().map(|(_, g)| g.y() + g.height()).max().unwrap();
+            let slices: Vec<_> = windows.into_iter()
+                .map(|(c, g)| (c, ((g.x() - x0) as f32, (g.y() - y0) as f32)))
+                .collect();
+            Canvas::start_spanning(&slices, &gdk::Rectangle::new(x0, y0, x1 - x0, y1 - y0));
+        } else {
+            for (canvas, geom) in windows {
+                canvas.start(&geom);
+            }
+        }
     });
 
     app.run_with_args::<&str>(&[]);
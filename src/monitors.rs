@@ -0,0 +1,66 @@
+use crate::config::settings;
+use crate::ipc;
+use crate::types::{Output, Region};
+use gtk4::gdk;
+use gtk4::prelude::*;
+
+/// Monitors to open overlay windows on, picked by `--monitor`. Exits with the list of
+/// available monitors when the requested one doesn't exist; `focused` and `pointer` fall
+/// back to the first monitor when the compositor can't be asked.
+pub fn select(display: &gdk::Display) -> Vec<gdk::Monitor> {
+    let all: Vec<gdk::Monitor> = display.monitors()
+        .iter::<gdk::Monitor>()
+        .filter_map(Result::ok)
+        .collect();
+    if all.is_empty() {
+        eprintln!("no monitors found");
+        std::process::exit(1);
+    }
+    // Overlays there are set up through the key window, so only one can be configured
+    if cfg!(target_os = "macos") && settings().monitor != Output::default() {
+        eprintln!("--monitor is only supported on Linux; macOS covers the screen of the key window");
+        std::process::exit(1);
+    }
+    let found = match &settings().monitor {
+        Output::All => return all,
+        Output::Index(i) => all.get(*i).cloned(),
+        Output::Connector(name) => by_connector(&all, name),
+        Output::Focused => {
+            let found = ipc::focused_output().and_then(|name| by_connector(&all, &name));
+            return vec![found.unwrap_or_else(|| all[0].clone())];
+        }
+        Output::Pointer => {
+            let found = ipc::pointer_output().and_then(|name| by_connector(&all, &name));
+            return vec![found.unwrap_or_else(|| all[0].clone())];
+        }
+    };
+    found.map(|m| vec![m]).unwrap_or_else(|| {
+        let names: Vec<String> = all.iter().enumerate()
+            .map(|(i, m)| format!("{i} ({})", m.connector().unwrap_or_default()))
+            .collect();
+        eprintln!("no such monitor; available: {}", names.join(", "));
+        std::process::exit(1);
+    })
+}
+
+/// Rectangle of the desktop layout an overlay on `monitor` covers: the whole monitor,
+/// or the `--region` inside it. `None` when the region is a focused window on another
+/// monitor; a window the compositor can't report leaves the whole monitor.
+pub fn area(monitor: &gdk::Monitor) -> Option<gdk::Rectangle> {
+    let geom = monitor.geometry();
+    let Some(region) = settings().region else { return Some(geom) };
+    if region == Region::Window {
+        let Some([x, y, w, h]) = ipc::focused_window() else { return Some(geom) };
+        let win = gdk::Rectangle::new(x.round() as i32, y.round() as i32, w.round() as i32, h.round() as i32);
+        return geom.intersect(&win);
+    }
+    let [x, y, w, h] = region.resolve(geom.width() as f64, geom.height() as f64);
+    Some(gdk::Rectangle::new(
+        geom.x() + x.round() as i32, geom.y() + y.round() as i32,
+        (w.round() as i32).max(1), (h.round() as i32).max(1),
+    ))
+}
+
+fn by_connector(monitors: &[gdk::Monitor], name: &str) -> Option<gdk::Monitor> {
+    monitors.iter().find(|m| m.connector().is_some_and(|c| c == name)).cloned()
+}
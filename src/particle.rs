@@ -1,7 +1,12 @@
-use crate::config::settings;
-use crate::types::{AnimType, Shape};
+use crate::config::{settings, ShapeMix};
+use crate::ipc;
+use crate::platform;
+use crate::render::{Assets, CairoRenderer, Renderer, SnapshotRenderer};
+use crate::shapes::{self, Glyphs, Seg};
+use crate::targets::{self, Target};
+use crate::types::{AnimType, Axis, Blend, Form, Origin, Rainbow, Shape};
 use gtk4::prelude::*;
-use gtk4::{gdk, gsk};
+use gtk4::{gdk, pango};
 use rand::Rng;
 
 fn randn(rng: &mut impl Rng, mean: f64, std: f64) -> f64 {
@@ -10,18 +15,134 @@ fn randn(rng: &mut impl Rng, mean: f64, std: f64) -> f64 {
     mean + std * (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
 }
 
+fn pick_mix<'a>(rng: &mut impl Rng, mix: &'a [ShapeMix]) -> Option<&'a ShapeMix> {
+    let total: f64 = mix.iter().map(|m| m.weight).sum();
+    if total <= 0.0 { return None; }
+    let mut x = rng.gen_range(0.0..total);
+    for m in mix {
+        if x < m.weight { return Some(m); }
+        x -= m.weight;
+    }
+    mix.last()
+}
+
+/// Linearly interpolates evenly spaced gradient stops at `f` in 0..=1.
+fn sample_gradient(stops: &[[f32; 3]], f: f32) -> [f32; 3] {
+    if stops.len() == 1 { return stops[0]; }
+    let pos = f * (stops.len() - 1) as f32;
+    let k = (pos as usize).min(stops.len() - 2);
+    let u = pos - k as f32;
+    let (a, b) = (stops[k], stops[k + 1]);
+    [a[0] + (b[0] - a[0]) * u, a[1] + (b[1] - a[1]) * u, a[2] + (b[2] - a[2]) * u]
+}
+
+/// RGB to (hue in degrees, saturation, value).
+fn rgb_to_hsv(c: [f32; 3]) -> (f32, f32, f32) {
+    let max = c[0].max(c[1]).max(c[2]);
+    let min = c[0].min(c[1]).min(c[2]);
+    let d = max - min;
+    let h = if d == 0.0 {
+        0.0
+    } else if max == c[0] {
+        60.0 * ((c[1] - c[2]) / d).rem_euclid(6.0)
+    } else if max == c[1] {
+        60.0 * ((c[2] - c[0]) / d + 2.0)
+    } else {
+        60.0 * ((c[0] - c[1]) / d + 4.0)
+    };
+    (h, if max == 0.0 { 0.0 } else { d / max }, max)
+}
+
+fn hsv_to_rgb(h: f32, s: f32, v: f32) -> [f32; 3] {
+    let h = h.rem_euclid(360.0) / 60.0;
+    let c = v * s;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = v - c;
+    [r + m, g + m, b + m]
+}
+
+/// Size and speed scale of depth layer `k`, spread evenly from the near to the far
+/// end of the configured depth range. Always 1 without layers.
+fn layer_scale(k: usize) -> f64 {
+    let s = settings();
+    if s.layers <= 1 { return 1.0; }
+    let [near, far] = s.depth;
+    near + (far - near) * k as f64 / (s.layers - 1) as f64
+}
+
+/// Opacity of depth layer `k`: the nearest is opaque and farther ones fade with their scale.
+pub fn layer_fade(k: usize) -> f32 {
+    (layer_scale(k) / settings().depth[0]).min(1.0) as f32
+}
+
+/// Configured emission point in simulation pixels, or `None` to keep each type's own
+/// launch points. A pointer or window the platform can't report also gives `None`.
+fn origin_point(world: &gdk::Rectangle) -> Option<[f64; 2]> {
+    let (w, h) = (world.width() as f64, world.height() as f64);
+    match settings().origin? {
+        Origin::Point(x, y) => Some([x.resolve(w), y.resolve(h)]),
+        Origin::Cursor => {
+            let (cx, cy) = platform::cursor_position()?;
+            Some([cx - world.x() as f64, cy - world.y() as f64])
+        }
+        Origin::Window => {
+            let [x, y, ww, wh] = ipc::focused_window()?;
+            Some([x + ww / 2.0 - world.x() as f64, y + wh / 2.0 - world.y() as f64])
+        }
+    }
+}
+
+/// Backdrop stars are a cool white whatever the palette
+const STAR_COLOR: [f32; 3] = [0.9, 0.93, 1.0];
+
+/// Points in each streamer's segment chain, head included
+const STREAMER_SEGS: usize = 12;
+
 pub struct Particles {
     x: Vec<f64>, y: Vec<f64>, vx: Vec<f64>, vy: Vec<f64>,
-    delay: Vec<f32>, color: Vec<u8>,
+    delay: Vec<f32>, color: Vec<[f32; 3]>,
     pw: Vec<f32>, ph: Vec<f32>,
     rot: Vec<f64>, rot_speed: Vec<f64>,
     wobble: Vec<f64>, wobble_speed: Vec<f64>,
-    is_circle: Vec<bool>,
+    shape: Vec<Shape>,
+    // Glyph index per particle; only meaningful where `shape` is `Glyph`
+    glyph: Vec<u32>,
+    // Segment chain per particle, STREAMER_SEGS points each; empty unless streamers are drawn
+    seg: Vec<[f64; 2]>,
+    // Texture per particle; only meaningful where `shape` is `Sprite`
+    sprite: Vec<u32>,
+    assets: Assets,
+    // Depth layer per particle, 0 nearest; all 0 unless layers are configured
+    layer: Vec<u8>,
+    // Particle indices in each depth layer, so drawing a layer skips the others
+    by_layer: Vec<Vec<usize>>,
+    // Meteor backdrop as x, y, size, twinkle phase and twinkle speed; empty for other types
+    stars: Vec<[f32; 5]>,
+    // Polar state around the vortex axis; empty for other types
+    ang: Vec<f64>, rad: Vec<f64>,
+    // Vortex axis position
+    center: [f64; 2],
+    // Resting positions when forming text; empty for plain effects
+    targets: Vec<Target>,
+    w: f64, h: f64,
 }
 
 impl Particles {
-    pub fn new(w: f64, h: f64) -> Self {
+    /// Simulation over `world`, a rectangle of the desktop layout; particle positions are
+    /// relative to its top-left corner.
+    pub fn new(world: &gdk::Rectangle, pango: &pango::Context) -> Self {
         let s = settings();
+        // Every spawn range below is proportional to the world, so keep it non-empty
+        let (w, h) = (world.width().max(1) as f64, world.height().max(1) as f64);
+        let origin = origin_point(world);
         let n = s.particles;
         let mut r = rand::thread_rng();
         let pi = std::f64::consts::PI;
@@ -29,37 +150,43 @@ impl Particles {
         let (mut x, mut y, mut vx, mut vy) =
             (Vec::with_capacity(n), Vec::with_capacity(n),
              Vec::with_capacity(n), Vec::with_capacity(n));
+        let (mut ang, mut rad) = (Vec::new(), Vec::new());
+        let center = origin.unwrap_or([w / 2.0, h / 2.0]);
 
         match s.anim_type {
             AnimType::Confetti => {
+                // Both bottom corners, or two mirrored fans from a single origin
+                let ([lx, ly], [rx, ry]) = origin.map_or(([0.0, h], [w, h]), |o| (o, o));
                 let half = n / 2;
                 for i in 0..n {
-                    let (cx, ang) = if i < half {
-                        (randn(&mut r, 0.0, 20.0), r.gen_range(-pi * 0.9..-pi * 0.1))
+                    let (cx, cy, ang) = if i < half {
+                        (lx, ly, r.gen_range(-pi * 0.9..-pi * 0.1))
                     } else {
-                        (randn(&mut r, w, 20.0), pi - r.gen_range(-pi * 0.9..-pi * 0.1))
+                        (rx, ry, pi - r.gen_range(-pi * 0.9..-pi * 0.1))
                     };
-                    x.push(cx);
-                    y.push(randn(&mut r, h, 10.0));
+                    x.push(randn(&mut r, cx, 20.0));
+                    y.push(randn(&mut r, cy, 10.0));
                     let spd = r.gen_range(s.speed_min..s.speed_max);
-                    vx.push(ang.cos() * spd + r.gen_range(-s.spread..s.spread));
+                    vx.push(ang.cos() * spd + r.gen_range(-s.spread..=s.spread));
                     vy.push(ang.sin() * spd);
                 }
             }
             AnimType::Cannon => {
+                let [ox, oy] = origin.unwrap_or([w / 2.0, h]);
                 for _ in 0..n {
-                    x.push(randn(&mut r, w / 2.0, 20.0));
-                    y.push(randn(&mut r, h, 10.0));
+                    x.push(randn(&mut r, ox, 20.0));
+                    y.push(randn(&mut r, oy, 10.0));
                     let ang = r.gen_range(-pi * 0.85..-pi * 0.15);
                     let spd = r.gen_range(s.speed_min..s.speed_max);
-                    vx.push(ang.cos() * spd + r.gen_range(-s.spread..s.spread));
+                    vx.push(ang.cos() * spd + r.gen_range(-s.spread..=s.spread));
                     vy.push(ang.sin() * spd);
                 }
             }
             AnimType::Pop => {
+                let [ox, oy] = origin.unwrap_or([w / 2.0, h / 2.0]);
                 for _ in 0..n {
-                    x.push(w / 2.0);
-                    y.push(h / 2.0);
+                    x.push(ox);
+                    y.push(oy);
                     let ang = r.gen_range(0.0..tau);
                     let spd = r.gen_range(s.speed_min..s.speed_max);
                     vx.push(ang.cos() * spd);
@@ -67,8 +194,10 @@ impl Particles {
                 }
             }
             AnimType::Fireworks => {
-                let cx = w / 2.0 + r.gen_range(-w * 0.15..w * 0.15);
-                let cy = h * 0.3 + r.gen_range(-h * 0.1..h * 0.1);
+                let [cx, cy] = origin.unwrap_or_else(|| [
+                    w / 2.0 + r.gen_range(-w * 0.15..w * 0.15),
+                    h * 0.3 + r.gen_range(-h * 0.1..h * 0.1),
+                ]);
                 for _ in 0..n {
                     x.push(cx + r.gen_range(-5.0..5.0));
                     y.push(cy + r.gen_range(-5.0..5.0));
@@ -82,7 +211,7 @@ impl Particles {
                 for _ in 0..n {
                     x.push(r.gen_range(0.0..w));
                     y.push(r.gen_range(-h * 0.3..h * 0.1));
-                    vx.push(r.gen_range(-s.spread..s.spread));
+                    vx.push(r.gen_range(-s.spread..=s.spread));
                     vy.push(r.gen_range(s.speed_min..s.speed_max));
                 }
             }
@@ -90,7 +219,7 @@ impl Particles {
                 for _ in 0..n {
                     x.push(r.gen_range(0.0..w));
                     y.push(r.gen_range(-h..0.0));
-                    vx.push(r.gen_range(-s.spread..s.spread));
+                    vx.push(r.gen_range(-s.spread..=s.spread));
                     vy.push(r.gen_range(s.speed_min..s.speed_max));
                 }
             }
@@ -103,52 +232,209 @@ impl Particles {
                 }
             }
             AnimType::Drop => {
+                let [ox, oy] = origin.unwrap_or([w / 2.0, 0.0]);
                 for _ in 0..n {
-                    x.push(randn(&mut r, w / 2.0, s.spread));
-                    y.push(r.gen_range(-80.0..20.0));
-                    vx.push(r.gen_range(-s.spread * 0.3..s.spread * 0.3));
+                    x.push(randn(&mut r, ox, s.spread));
+                    y.push(oy + r.gen_range(-80.0..20.0));
+                    vx.push(r.gen_range(-s.spread * 0.3..=s.spread * 0.3));
                     vy.push(r.gen_range(s.speed_min..s.speed_max));
                 }
             }
+            AnimType::Meteors => {
+                // Heads enter from above and cross the screen diagonally down-left
+                for _ in 0..n {
+                    x.push(r.gen_range(w * 0.2..w * 1.3));
+                    y.push(r.gen_range(-h * 0.3..h * 0.1));
+                    let ang = pi * 0.75 + r.gen_range(-0.12..0.12);
+                    let spd = r.gen_range(s.speed_min..s.speed_max);
+                    vx.push(ang.cos() * spd);
+                    vy.push(ang.sin() * spd);
+                }
+            }
+            AnimType::Vortex => {
+                let r_max = w.hypot(h) / 2.0;
+                // Converging vortices start spread out, outward ones start near the axis
+                let (r_lo, r_hi) = match (s.axis, s.pull >= 0.0) {
+                    (Axis::Center, true) => (r_max * 0.3, r_max),
+                    (Axis::Center, false) => (0.0, r_max * 0.15),
+                    (Axis::Vertical, true) => (w * 0.1, w * 0.4),
+                    (Axis::Vertical, false) => (0.0, w * 0.08),
+                };
+                for _ in 0..n {
+                    let a = r.gen_range(0.0..tau);
+                    let rd = r.gen_range(r_lo..r_hi);
+                    let (px, py) = match s.axis {
+                        Axis::Center => (center[0] + a.cos() * rd, center[1] + a.sin() * rd),
+                        Axis::Vertical => (center[0] + a.cos() * rd, r.gen_range(h * 0.2..h * 1.1)),
+                    };
+                    x.push(px);
+                    y.push(py);
+                    vx.push(0.0);
+                    vy.push(-r.gen_range(s.speed_min..s.speed_max));
+                    ang.push(a);
+                    rad.push(rd);
+                }
+            }
+            AnimType::Coins => {
+                for _ in 0..n {
+                    x.push(r.gen_range(0.0..w));
+                    y.push(r.gen_range(-h * 0.6 - 20.0..-20.0));
+                    vx.push(r.gen_range(-s.spread..=s.spread));
+                    vy.push(r.gen_range(s.speed_min..s.speed_max));
+                }
+            }
+            AnimType::Fountain => {
+                let [ox, oy] = origin.unwrap_or([w / 2.0, h]);
+                let base = -pi / 2.0 + s.angle.to_radians();
+                let half_cone = s.cone.to_radians() / 2.0;
+                for _ in 0..n {
+                    x.push(ox + r.gen_range(-5.0..5.0));
+                    y.push(oy);
+                    let ang = base + r.gen_range(-half_cone..=half_cone);
+                    let spd = r.gen_range(s.speed_min..s.speed_max);
+                    vx.push(ang.cos() * spd + r.gen_range(-s.spread..=s.spread));
+                    vy.push(ang.sin() * spd);
+                }
+            }
         }
 
-        let nc = s.colors.len();
         let (pw_lo, pw_hi, ph_lo, ph_hi): (f32, f32, f32, f32) = match s.anim_type {
             AnimType::Fireworks => (3.0, 6.0, 3.0, 6.0),
             AnimType::Snow      => (4.0, 8.0, 4.0, 8.0),
             AnimType::Rain      => (1.5, 3.0, 15.0, 30.0),
             AnimType::Sparkle   => (2.0, 5.0, 2.0, 5.0),
+            AnimType::Meteors   => (3.0, 5.0, 3.0, 5.0),
+            AnimType::Coins     => (12.0, 20.0, 12.0, 20.0),
             _                   => (5.0, 12.0, 3.0, 8.0),
         };
         let (rot_lo, rot_hi): (f64, f64) = match s.anim_type {
-            AnimType::Rain | AnimType::Sparkle => (0.0, 0.0),
+            AnimType::Rain | AnimType::Sparkle | AnimType::Meteors => (0.0, 0.0),
             AnimType::Snow => (-3.0, 3.0),
+            AnimType::Coins => (-1.5, 1.5),
             _ => (-12.0, 12.0),
         };
         let delay_max: f32 = match s.anim_type {
             AnimType::Snow | AnimType::Rain => 1.5,
             AnimType::Sparkle => s.duration as f32 * 0.6,
+            AnimType::Meteors => s.duration as f32 * 0.7,
             AnimType::Drop => 0.5,
+            AnimType::Coins => 1.0,
             _ => 0.3,
         };
 
-        let is_circle: Vec<bool> = match s.shape {
-            Shape::Rect => vec![false; n],
-            Shape::Circle => vec![true; n],
-            Shape::Mixed => (0..n).map(|_| r.gen_bool(0.5)).collect(),
+        let targets = if let Some(ref path) = s.image {
+            targets::from_image(path, w, h, n)
+        } else if let Some(ref text) = s.text {
+            targets::from_text(pango, text, w, h, n)
+        } else {
+            Vec::new()
+        };
+        // Weighted mixes pick an entry per particle, which may carry its own size range and colors
+        let mix: Vec<Option<&ShapeMix>> = (0..n).map(|_| pick_mix(&mut r, &s.shapes)).collect();
+        let shape: Vec<Shape> = mix.iter()
+            .map(|m| match m.map_or(s.shape, |m| m.shape) {
+                Shape::Mixed => if r.gen_bool(0.5) { Shape::Circle } else { Shape::Rect },
+                shape => shape,
+            })
+            .collect();
+
+        // Image pixels bring their own colors; everything else draws from the palette
+        let color: Vec<[f32; 3]> = (0..n).map(|i| match targets.get(i).and_then(|t| t.color) {
+            Some(c) => c,
+            None => {
+                let palette = mix[i].and_then(|m| m.colors.as_deref()).unwrap_or(&s.colors);
+                palette[r.gen_range(0..palette.len())]
+            }
+        }).collect();
+
+        let glyphs = Glyphs::new(pango, &s.glyphs);
+        let glyph: Vec<u32> = if shape.contains(&Shape::Glyph) && glyphs.len() > 0 {
+            (0..n).map(|_| r.gen_range(0..glyphs.len()) as u32).collect()
+        } else {
+            Vec::new()
+        };
+
+        let sprites: Vec<gdk::Texture> = s.sprites.iter()
+            .map(|path| gdk::Texture::from_file(&gtk4::gio::File::for_path(path)).unwrap_or_else(|e| {
+                eprintln!("failed to load sprite {}: {e}", path.display());
+                std::process::exit(1);
+            }))
+            .collect();
+        if shape.contains(&Shape::Sprite) && sprites.is_empty() {
+            eprintln!("shape sprite needs --sprites");
+            std::process::exit(1);
+        }
+        let sprite: Vec<u32> = if shape.contains(&Shape::Sprite) {
+            (0..n).map(|_| r.gen_range(0..sprites.len()) as u32).collect()
+        } else {
+            Vec::new()
+        };
+
+        // Streamer chains start bunched at the head and unfurl as it flies
+        let seg: Vec<[f64; 2]> = if shape.contains(&Shape::Streamer) {
+            (0..n * STREAMER_SEGS).map(|k| [x[k / STREAMER_SEGS], y[k / STREAMER_SEGS]]).collect()
+        } else {
+            Vec::new()
         };
 
+        let mut pw: Vec<f32> = (0..n).map(|_| r.gen_range(pw_lo..pw_hi)).collect();
+        let mut ph: Vec<f32> = (0..n).map(|_| r.gen_range(ph_lo..ph_hi)).collect();
+        for (i, m) in mix.iter().enumerate() {
+            if let Some([lo, hi]) = m.and_then(|m| m.size) {
+                // Keep the type's aspect ratio while rescaling to the entry's size range
+                let k = r.gen_range(lo..=hi) / pw[i];
+                pw[i] *= k;
+                ph[i] *= k;
+            }
+        }
+        // Farther layers are smaller and slower; the scale stays applied through `step`
+        let layer: Vec<u8> = (0..n).map(|_| r.gen_range(0..s.layers) as u8).collect();
+        if s.layers > 1 {
+            for i in 0..n {
+                let k = layer_scale(layer[i] as usize);
+                pw[i] *= k as f32;
+                ph[i] *= k as f32;
+                vx[i] *= k;
+                vy[i] *= k;
+            }
+        }
+        let mut by_layer = vec![Vec::new(); s.layers];
+        for (i, &k) in layer.iter().enumerate() {
+            by_layer[k as usize].push(i);
+        }
+        if s.form == Form::Burst && !targets.is_empty() {
+            // Appear already in formation
+            for (i, t) in targets.iter().enumerate() {
+                x[i] = t.x;
+                y[i] = t.y;
+            }
+        }
+
+        // Fountains emit continuously at a fixed rate instead of in one burst
+        let delay: Vec<f32> = match s.anim_type {
+            _ if s.form == Form::Burst && !targets.is_empty() => vec![0.0; n],
+            AnimType::Fountain => (0..n).map(|i| (i as f64 / s.rate) as f32).collect(),
+            _ => (0..n).map(|_| r.gen_range(0.0..delay_max)).collect(),
+        };
+
+        let stars_n = if s.anim_type == AnimType::Meteors { s.stars } else { 0 };
+        let stars = (0..stars_n)
+            .map(|_| [
+                r.gen_range(0.0..w) as f32, r.gen_range(0.0..h) as f32, r.gen_range(1.0..2.5),
+                r.gen_range(0.0..tau) as f32, r.gen_range(1.0..3.0),
+            ])
+            .collect();
+
         Self {
-            x, y, vx, vy,
-            delay: (0..n).map(|_| r.gen_range(0.0..delay_max)).collect(),
-            color: (0..n).map(|_| r.gen_range(0..nc as u8)).collect(),
-            pw: (0..n).map(|_| r.gen_range(pw_lo..pw_hi)).collect(),
-            ph: (0..n).map(|_| r.gen_range(ph_lo..ph_hi)).collect(),
+            x, y, vx, vy, delay,
+            color, pw, ph,
             rot: (0..n).map(|_| r.gen_range(0.0..tau)).collect(),
             rot_speed: (0..n).map(|_| if rot_lo == rot_hi { rot_lo } else { r.gen_range(rot_lo..rot_hi) }).collect(),
             wobble: (0..n).map(|_| r.gen_range(0.0..tau)).collect(),
             wobble_speed: (0..n).map(|_| r.gen_range(3.0..8.0)).collect(),
-            is_circle,
+            shape, glyph, seg, sprite,
+            assets: Assets::new(glyphs, sprites),
+            layer, by_layer, stars, ang, rad, center, targets, w, h,
         }
     }
 
@@ -156,21 +442,70 @@ impl Particles {
         let s = settings();
         let n = s.particles;
         let tf = t as f32;
+        if !self.targets.is_empty() {
+            self.step_targets(dt, t);
+            self.step_streamers(dt, t);
+            return;
+        }
         for i in 0..n {
             if tf < self.delay[i] { continue; }
+            let k = layer_scale(self.layer[i] as usize);
             match s.anim_type {
                 AnimType::Sparkle => {
                     self.wobble[i] += self.wobble_speed[i] * dt;
                 }
                 AnimType::Snow => {
-                    self.x[i] += self.wobble[i].sin() * 30.0 * dt;
+                    self.x[i] += self.wobble[i].sin() * 30.0 * k * dt;
                     self.wobble[i] += self.wobble_speed[i] * dt;
-                    self.vy[i] += s.gravity * dt;
+                    self.vy[i] += s.gravity * k * dt;
                     self.y[i] += self.vy[i] * dt;
                     self.rot[i] += self.rot_speed[i] * dt;
                 }
+                AnimType::Coins => {
+                    self.vy[i] += s.gravity * k * dt;
+                    self.vx[i] *= s.drag;
+                    self.x[i] += self.vx[i] * dt;
+                    self.y[i] += self.vy[i] * dt;
+                    self.wobble[i] += self.wobble_speed[i] * dt;
+                    let floor = self.h - self.pw[i] as f64 / 2.0;
+                    if self.y[i] >= floor {
+                        self.y[i] = floor;
+                        if self.vy[i] > 40.0 {
+                            self.vy[i] *= -s.bounce;
+                            self.vx[i] *= 0.8;
+                            self.wobble_speed[i] *= 0.7;
+                        } else {
+                            // Resting on the floor: settle and slowly stop spinning
+                            self.vy[i] = 0.0;
+                            self.vx[i] *= 1.0 - (4.0 * dt).min(1.0);
+                            self.wobble_speed[i] *= 1.0 - (2.0 * dt).min(1.0);
+                        }
+                    } else {
+                        self.rot[i] += self.rot_speed[i] * dt;
+                    }
+                }
+                AnimType::Vortex => {
+                    // Angular speed grows towards the axis like a real whirlpool
+                    let r_ref = self.w.min(self.h) / 4.0;
+                    let boost = (r_ref / self.rad[i].max(10.0)).sqrt().min(4.0);
+                    self.ang[i] += s.spin * boost * k * dt;
+                    self.rad[i] = (self.rad[i] - s.pull * k * dt).max(0.0);
+                    let (sin, cos) = self.ang[i].sin_cos();
+                    match s.axis {
+                        Axis::Center => {
+                            self.x[i] = self.center[0] + cos * self.rad[i];
+                            self.y[i] = self.center[1] + sin * self.rad[i];
+                        }
+                        Axis::Vertical => {
+                            self.x[i] = self.center[0] + cos * self.rad[i];
+                            self.y[i] += self.vy[i] * dt;
+                        }
+                    }
+                    self.rot[i] += self.rot_speed[i] * dt;
+                    self.wobble[i] += self.wobble_speed[i] * dt;
+                }
                 _ => {
-                    self.vy[i] += s.gravity * dt;
+                    self.vy[i] += s.gravity * k * dt;
                     self.vx[i] *= s.drag;
                     self.x[i] += self.vx[i] * dt;
                     self.y[i] += self.vy[i] * dt;
@@ -179,67 +514,296 @@ impl Particles {
                 }
             }
         }
+        self.step_streamers(dt, t);
     }
 
-    pub fn draw(&self, snap: &gtk4::Snapshot, alpha: f32, t: f64) {
+    /// Drags each streamer's segment chain after its head: every link keeps at most one
+    /// segment length from the one before, sagging and curling a little as it trails.
+    fn step_streamers(&mut self, dt: f64, t: f64) {
+        if self.seg.is_empty() { return; }
+        let tf = t as f32;
+        for i in 0..self.shape.len() {
+            if self.shape[i] != Shape::Streamer || tf < self.delay[i] { continue; }
+            let len = self.ph[i] as f64 * 1.5;
+            let chain = &mut self.seg[i * STREAMER_SEGS..(i + 1) * STREAMER_SEGS];
+            chain[0] = [self.x[i], self.y[i]];
+            for k in 1..STREAMER_SEGS {
+                let [px, py] = chain[k - 1];
+                let [mut x, mut y] = chain[k];
+                let (dx, dy) = (x - px, y - py);
+                let d = dx.hypot(dy);
+                if d > 1e-6 {
+                    // Curl sideways around the link direction, plus a gentle sag
+                    let curl = (self.wobble[i] + k as f64 * 0.8).sin() * 40.0 * dt;
+                    x += -dy / d * curl;
+                    y += dx / d * curl + 30.0 * dt;
+                }
+                let (dx, dy) = (x - px, y - py);
+                let d = dx.hypot(dy);
+                if d > len {
+                    x = px + dx / d * len;
+                    y = py + dy / d * len;
+                }
+                chain[k] = [x, y];
+            }
+        }
+    }
+
+    /// Fly into the targets (or appear on them), hold the formed shape, then scatter ballistically.
+    fn step_targets(&mut self, dt: f64, t: f64) {
         let s = settings();
-        let n = s.particles;
+        let (hold_t, scatter_t, blast) = match s.form {
+            Form::Assemble => (s.duration * 0.35, s.duration * 0.7, 0.5),
+            Form::Burst => (0.0, s.duration * 0.4, 1.0),
+        };
         let tf = t as f32;
+        if t >= scatter_t && t - dt < scatter_t {
+            // Blast outward from the middle of the formed shape
+            let mut r = rand::thread_rng();
+            let (cx, cy) = (self.w / 2.0, self.h / 2.0);
+            for i in 0..s.particles {
+                let ang = (self.y[i] - cy).atan2(self.x[i] - cx) + r.gen_range(-0.3..0.3);
+                let spd = r.gen_range(s.speed_min..=s.speed_max.max(s.speed_min)) * blast;
+                self.vx[i] = ang.cos() * spd;
+                self.vy[i] = ang.sin() * spd;
+            }
+        }
+        for i in 0..s.particles {
+            if tf < self.delay[i] { continue; }
+            if t < scatter_t {
+                // Ease towards the target, settled by the time the hold starts
+                let k = 1.0 - (-8.0 * dt / hold_t.max(0.1)).exp();
+                let dx = (self.targets[i].x - self.x[i]) * k;
+                let dy = (self.targets[i].y - self.y[i]) * k;
+                self.x[i] += dx;
+                self.y[i] += dy;
+                // Track the effective velocity so trails follow the flight in
+                if dt > 0.0 {
+                    self.vx[i] = dx / dt;
+                    self.vy[i] = dy / dt;
+                }
+            } else {
+                self.vy[i] += s.gravity * dt;
+                self.vx[i] *= s.drag;
+                self.x[i] += self.vx[i] * dt;
+                self.y[i] += self.vy[i] * dt;
+            }
+            self.rot[i] += self.rot_speed[i] * dt;
+            self.wobble[i] += self.wobble_speed[i] * dt;
+        }
+    }
 
-        if s.anim_type == AnimType::Sparkle {
-            for i in 0..n {
-                if tf < self.delay[i] { continue; }
-                let phase = (self.wobble[i].sin() * 0.5 + 0.5) as f32;
-                let pa = alpha * phase;
-                if pa < 0.01 { continue; }
-                let c = &s.colors[self.color[i] as usize];
-                let rgba = gdk::RGBA::new(c[0], c[1], c[2], pa);
-                let sz = self.pw[i];
-                let half = sz / 2.0;
-                let rect = graphene::Rect::new(-half, -half, sz, sz);
-                snap.save();
-                snap.translate(&graphene::Point::new(self.x[i] as f32, self.y[i] as f32));
-                if self.is_circle[i] {
-                    let corner = graphene::Size::new(half, half);
-                    snap.push_rounded_clip(&gsk::RoundedRect::new(rect, corner, corner, corner, corner));
-                    snap.append_color(&rgba, &rect);
-                    snap.pop();
-                } else {
-                    snap.append_color(&rgba, &rect);
-                }
-                snap.restore();
+    /// Outline of streamer `i` as a smooth ribbon along its segment chain, narrowing where
+    /// it twists edge-on to the viewer.
+    fn streamer_outline(&self, i: usize) -> Vec<Seg> {
+        let chain = &self.seg[i * STREAMER_SEGS..(i + 1) * STREAMER_SEGS];
+        let half = self.pw[i] as f64 * 0.6;
+        let mut left = Vec::with_capacity(STREAMER_SEGS);
+        let mut right = Vec::with_capacity(STREAMER_SEGS);
+        for k in 0..STREAMER_SEGS {
+            let [ax, ay] = chain[k.saturating_sub(1)];
+            let [bx, by] = chain[(k + 1).min(STREAMER_SEGS - 1)];
+            let (dx, dy) = (bx - ax, by - ay);
+            let d = dx.hypot(dy).max(1e-6);
+            let twist = (self.wobble[i] * 0.5 + k as f64 * 0.5).cos().abs().max(0.12);
+            let (nx, ny) = (-dy / d * half * twist, dx / d * half * twist);
+            let [x, y] = chain[k];
+            left.push(((x + nx) as f32, (y + ny) as f32));
+            right.push(((x - nx) as f32, (y - ny) as f32));
+        }
+        shapes::strip(&left, &right)
+    }
+
+    /// Particle `i`'s color at time `t`, after any lifetime gradient and hue cycling.
+    fn color_at(&self, i: usize, t: f64) -> [f32; 3] {
+        let s = settings();
+        let mut c = self.color[i];
+        if !s.gradient.is_empty() {
+            let start = self.delay[i] as f64;
+            let life = ((t - start) / (s.duration - start).max(1e-3)).clamp(0.0, 1.0) as f32;
+            c = sample_gradient(&s.gradient, life);
+        }
+        match s.rainbow {
+            Some(Rainbow::Time) => {
+                // White and grey palettes have no hue to rotate, so lift them to the
+                // saturation `Position` uses
+                let (h, sat, v) = rgb_to_hsv(c);
+                c = hsv_to_rgb(h + (t * s.hue_speed) as f32, sat.max(0.85), v);
             }
-            return;
+            Some(Rainbow::Position) => {
+                let h = (self.x[i] / self.w * 360.0 + t * s.hue_speed) as f32;
+                c = hsv_to_rgb(h, 0.85, 1.0);
+            }
+            None => {}
         }
+        c
+    }
 
-        let rgba: Vec<gdk::RGBA> = s.colors.iter()
-            .map(|c| gdk::RGBA::new(c[0], c[1], c[2], alpha))
-            .collect();
-        let use_wobble = !matches!(s.anim_type, AnimType::Rain);
-        for i in 0..n {
-            if tf < self.delay[i] { continue; }
-            let color = &rgba[self.color[i] as usize];
-            snap.save();
-            snap.translate(&graphene::Point::new(self.x[i] as f32, self.y[i] as f32));
-            snap.rotate(self.rot[i].to_degrees() as f32);
-            if self.is_circle[i] {
-                let sz = (self.pw[i] + self.ph[i]) / 2.0;
-                let half = sz / 2.0;
-                let rect = graphene::Rect::new(-half, -half, sz, sz);
-                let corner = graphene::Size::new(half, half);
-                snap.push_rounded_clip(&gsk::RoundedRect::new(rect, corner, corner, corner, corner));
-                snap.append_color(color, &rect);
-                snap.pop();
+    /// Draws every depth layer back to front, blurring and fading the farther ones. GSK has
+    /// no additive or screen blending between siblings, so those layers paint through cairo.
+    /// `view` is the slice of the simulation to show, in simulation coordinates; it lands at
+    /// the snapshot's origin.
+    pub fn draw(&self, snap: &gtk4::Snapshot, alpha: f32, t: f64, view: &graphene::Rect) {
+        let s = settings();
+        snap.save();
+        snap.translate(&graphene::Point::new(-view.x(), -view.y()));
+        let blend_surface = || {
+            let cr = snap.append_cairo(view);
+            cr.set_operator(match s.blend {
+                Blend::Screen => cairo::Operator::Screen,
+                _ => cairo::Operator::Add,
+            });
+            cr
+        };
+        // Blur applies per layer, so only unblurred layers can share one cairo surface
+        let blurred = s.layers > 1 && s.depth_blur > 0.0;
+        let shared = (s.blend != Blend::Normal && !blurred).then(blend_surface);
+        for k in (0..s.layers).rev() {
+            let blur = if blurred { s.depth_blur * k as f64 / (s.layers - 1) as f64 } else { 0.0 };
+            let fade = layer_fade(k);
+            if blur > 0.0 { snap.push_blur(blur); }
+            if let Some(cr) = &shared {
+                self.render(&mut CairoRenderer::new(cr, &self.assets), alpha * fade, t, k as u8);
+            } else if s.blend == Blend::Normal {
+                let mut r = SnapshotRenderer::new(snap, &self.assets);
+                self.render(&mut r, alpha * fade, t, k as u8);
+                r.finish();
             } else {
-                let sw = if use_wobble {
-                    self.wobble[i].sin().abs().max(0.15) as f32 * self.pw[i]
-                } else {
-                    self.pw[i]
-                };
-                let sh = self.ph[i];
-                snap.append_color(color, &graphene::Rect::new(-sw / 2.0, -sh / 2.0, sw, sh));
+                let cr = blend_surface();
+                self.render(&mut CairoRenderer::new(&cr, &self.assets), alpha * fade, t, k as u8);
+            }
+            if blur > 0.0 { snap.pop(); }
+        }
+        snap.restore();
+    }
+
+    /// Emits draw commands for the particles in depth layer `layer`.
+    pub fn render(&self, r: &mut dyn Renderer, alpha: f32, t: f64, layer: u8) {
+        let s = settings();
+        let tf = t as f32;
+        let sparkle = s.anim_type == AnimType::Sparkle;
+        let vortex = s.anim_type == AnimType::Vortex;
+        let use_wobble = !matches!(s.anim_type, AnimType::Rain | AnimType::Sparkle);
+        // Vortex positions are driven by polar state, so vx/vy don't describe their motion
+        let trails = s.trail > 0.0 && !vortex && !sparkle;
+        // Backdrop stars sit behind the farthest layer and twinkle like sparkles
+        if layer as usize == s.layers - 1 {
+            for &[x, y, sz, phase, speed] in &self.stars {
+                let a = alpha * ((phase + tf * speed).sin() * 0.5 + 0.5) * 0.7;
+                if a < 0.01 { continue; }
+                r.save();
+                r.translate(x, y);
+                r.shape(Shape::Circle, sz, sz, &STAR_COLOR, a);
+                r.restore();
+            }
+        }
+        for &i in &self.by_layer[layer as usize] {
+            if tf < self.delay[i] { continue; }
+            // Sparkles twinkle by pulsing their opacity
+            let a = if sparkle { alpha * (self.wobble[i].sin() * 0.5 + 0.5) as f32 } else { alpha };
+            if a < 0.01 { continue; }
+            let c = &self.color_at(i, t);
+            let (vx, vy) = (self.vx[i], self.vy[i]);
+            match s.anim_type {
+                AnimType::Meteors => {
+                    let sz = self.pw[i];
+                    r.save();
+                    r.translate(self.x[i] as f32, self.y[i] as f32);
+                    r.rotate(vy.atan2(vx) as f32);
+                    r.streak(c, a, (vx.hypot(vy) * s.trail).min(600.0) as f32, sz);
+                    r.shape(Shape::Circle, sz, sz, c, a);
+                    r.restore();
+                    continue;
+                }
+                AnimType::Coins => {
+                    // Spinning about the vertical axis flattens the disc to its edge and back,
+                    // sweeping a metallic highlight across the face
+                    let (sin, cos) = self.wobble[i].sin_cos();
+                    let d = self.pw[i];
+                    r.save();
+                    r.translate(self.x[i] as f32, self.y[i] as f32);
+                    r.rotate(self.rot[i] as f32);
+                    r.coin((cos.abs() as f32).max(0.08) * d, d, c, a, 0.6 + 0.4 * cos.abs() as f32, 0.5 + 0.35 * sin as f32);
+                    r.restore();
+                    continue;
+                }
+                _ => {}
+            }
+            if self.shape[i] == Shape::Streamer && !sparkle {
+                r.path(&self.streamer_outline(i), c, a);
+                continue;
+            }
+            // Sparkles draw every shape at their base size
+            let base = if sparkle { self.pw[i] } else { (self.pw[i] + self.ph[i]) / 2.0 };
+            r.save();
+            r.translate(self.x[i] as f32, self.y[i] as f32);
+            if s.glow > 0.0 {
+                r.glow(c, a, base * s.glow as f32);
+            }
+            if trails {
+                r.save();
+                r.rotate(vy.atan2(vx) as f32);
+                r.streak(c, a, (vx.hypot(vy) * s.trail).min(600.0) as f32, base * 0.6);
+                r.restore();
             }
-            snap.restore();
+            if s.anim_type == AnimType::Rain {
+                // Streaks line up with the direction of travel, so drops slant with the wind
+                r.rotate((vy.atan2(vx) - std::f64::consts::FRAC_PI_2) as f32);
+            } else if !sparkle {
+                r.rotate(self.rot[i] as f32);
+            }
+            if vortex {
+                // Shrink pieces as they get sucked into the axis
+                let k = (self.rad[i] / 60.0).min(1.0) as f32;
+                r.scale(k, k);
+            }
+            let flip = if use_wobble { self.wobble[i].sin().abs().max(0.15) as f32 } else { 1.0 };
+            let sz = if sparkle { base } else { base * 2.5 };
+            match self.shape[i] {
+                Shape::Rect | Shape::Mixed | Shape::Streamer if sparkle => r.shape(Shape::Rect, base, base, c, a),
+                Shape::Rect | Shape::Mixed | Shape::Streamer => r.shape(Shape::Rect, self.pw[i] * flip, self.ph[i], c, a),
+                Shape::Circle => r.shape(Shape::Circle, base, base, c, a),
+                Shape::Glyph => {
+                    if let Some(&idx) = self.glyph.get(i) { r.glyph(idx, sz * flip, sz, c, a); }
+                }
+                Shape::Sprite => {
+                    if let Some(&idx) = self.sprite.get(i) { r.sprite(idx, sz, flip, c, a); }
+                }
+                shape => {
+                    // Path shapes are unit-sized; tumbling flattens them like the rects
+                    let sz = if sparkle { base } else { base * 1.4 };
+                    let (sw, sh) = if shape == Shape::Ribbon && !sparkle { (sz * 0.6, sz * 2.5) } else { (sz, sz) };
+                    r.shape(shape, sw * flip, sh, c, a);
+                }
+            }
+            r.restore();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{set_settings, Cli, FileConfig, Settings};
+    use clap::Parser;
+
+    #[test]
+    fn cairo_renderer_paints_an_image_surface() {
+        let cli = Cli::try_parse_from(["confet", "pop", "-s", "circle", "-n", "200"]).unwrap();
+        set_settings(Settings::resolve(cli, FileConfig::default()));
+        let ps = Particles::new(&gdk::Rectangle::new(0, 0, 200, 200), &pango::Context::new());
+
+        let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 200, 200).unwrap();
+        {
+            let cr = cairo::Context::new(&surface).unwrap();
+            // Past every launch delay, with the burst still at its starting point
+            ps.render(&mut CairoRenderer::new(&cr, &ps.assets), 1.0, 1.0, 0);
         }
+        surface.flush();
+        let data = surface.data().unwrap();
+        let painted = data.chunks_exact(4)
+            .filter(|px| u32::from_ne_bytes([px[0], px[1], px[2], px[3]]) >> 24 > 0)
+            .count();
+        assert!(painted > 100, "only {painted} pixels painted");
     }
 }
@@ -1,18 +1,38 @@
 use gtk4::prelude::*;
+use gtk4::gdk;
 use gtk4_layer_shell::LayerShell;
+use std::os::unix::process::CommandExt;
+use super::x11;
 
-pub fn setup_window(win: &gtk4::Window) {
-    win.init_layer_shell();
-    win.set_layer(gtk4_layer_shell::Layer::Overlay);
-    for edge in [
-        gtk4_layer_shell::Edge::Top, gtk4_layer_shell::Edge::Bottom,
-        gtk4_layer_shell::Edge::Left, gtk4_layer_shell::Edge::Right,
-    ] {
-        win.set_anchor(edge, true);
+/// Checks that `display` can show overlays. Wayland compositors without wlr-layer-shell
+/// (such as GNOME) can't, so confet runs itself again on XWayland when it's available.
+pub fn init(display: &gdk::Display) {
+    if gtk4_layer_shell::is_supported() || x11::is_x11(display) { return; }
+    if std::env::var_os("DISPLAY").is_none() || std::env::var_os("GDK_BACKEND").is_some_and(|b| b == "x11") {
+        eprintln!("the compositor doesn't support wlr-layer-shell and no X11 display is available");
+        std::process::exit(1);
+    }
+    let exe = std::env::current_exe().unwrap_or_else(|e| {
+        eprintln!("failed to locate confet for the X11 fallback: {e}");
+        std::process::exit(1);
+    });
+    let e = std::process::Command::new(exe)
+        .args(std::env::args_os().skip(1))
+        .env("GDK_BACKEND", "x11")
+        .exec();
+    eprintln!("failed to restart on X11: {e}");
+    std::process::exit(1);
+}
+
+/// Turns `win` into a click-through overlay covering `area` of `monitor`, both in
+/// desktop layout coordinates. Uses layer-shell when the compositor has it and an
+/// override-redirect X11 window otherwise.
+pub fn setup_window(win: &gtk4::Window, monitor: &gdk::Monitor, area: &gdk::Rectangle) {
+    if gtk4_layer_shell::is_supported() {
+        setup_layer_shell(win, monitor, area);
+    } else {
+        x11::setup_window(win, area);
     }
-    win.set_exclusive_zone(-1);
-    win.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::None);
-    win.set_namespace(Some("confetti"));
 
     win.connect_realize(|w| {
         if let Some(surface) = w.surface() {
@@ -23,3 +43,34 @@ pub fn setup_window(win: &gtk4::Window) {
         }
     });
 }
+
+fn setup_layer_shell(win: &gtk4::Window, monitor: &gdk::Monitor, area: &gdk::Rectangle) {
+    win.init_layer_shell();
+    win.set_monitor(Some(monitor));
+    win.set_layer(gtk4_layer_shell::Layer::Overlay);
+    let geom = monitor.geometry();
+    if *area == geom {
+        for edge in [
+            gtk4_layer_shell::Edge::Top, gtk4_layer_shell::Edge::Bottom,
+            gtk4_layer_shell::Edge::Left, gtk4_layer_shell::Edge::Right,
+        ] {
+            win.set_anchor(edge, true);
+        }
+    } else {
+        // Pinned to the top-left corner, offset by margins and sized by the window
+        for edge in [gtk4_layer_shell::Edge::Top, gtk4_layer_shell::Edge::Left] {
+            win.set_anchor(edge, true);
+        }
+        win.set_margin(gtk4_layer_shell::Edge::Left, area.x() - geom.x());
+        win.set_margin(gtk4_layer_shell::Edge::Top, area.y() - geom.y());
+        win.set_default_size(area.width(), area.height());
+    }
+    win.set_exclusive_zone(-1);
+    win.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::None);
+    win.set_namespace(Some("confetti"));
+}
+
+/// Pointer position in the desktop layout, when the compositor will tell.
+pub fn cursor_position() -> Option<(f64, f64)> {
+    crate::ipc::cursor_position()
+}
@@ -16,16 +16,39 @@ struct CGSize { width: f64, height: f64 }
 #[derive(Copy, Clone)]
 struct CGRect { origin: CGPoint, size: CGSize }
 
-pub fn setup_window(win: &gtk4::Window) {
+/// Nothing to check: every macOS window can float above the desktop.
+pub fn init(_display: &gtk4::gdk::Display) {}
+
+/// Covers `area` of the key window's screen. `monitors::select` rejects `--monitor` here,
+/// so there is only one window and `area` is taken relative to `monitor`.
+pub fn setup_window(win: &gtk4::Window, monitor: &gtk4::gdk::Monitor, area: &gtk4::gdk::Rectangle) {
     win.set_decorated(false);
 
+    let geom = monitor.geometry();
+    let inset = (*area != geom).then(|| CGRect {
+        origin: CGPoint { x: (area.x() - geom.x()) as f64, y: (area.y() - geom.y()) as f64 },
+        size: CGSize { width: area.width() as f64, height: area.height() as f64 },
+    });
+
     // Configure the underlying NSWindow after GTK realizes it
-    win.connect_realize(|_| {
-        glib::idle_add_once(configure_ns_window);
+    win.connect_realize(move |_| {
+        glib::idle_add_once(move || configure_ns_window(inset));
     });
 }
 
-fn configure_ns_window() {
+/// Pointer position with the origin at the top-left of the main screen, as GDK lays out monitors.
+pub fn cursor_position() -> Option<(f64, f64)> {
+    unsafe {
+        // Cocoa puts the origin at the bottom-left of the main screen
+        let loc: CGPoint = msg_send![AnyClass::get("NSEvent")?, mouseLocation];
+        let screen: Option<Retained<AnyObject>> = msg_send_id![AnyClass::get("NSScreen")?, mainScreen];
+        let frame: CGRect = msg_send![&*screen?, frame];
+        Some((loc.x, frame.size.height - loc.y))
+    }
+}
+
+/// `inset` is a rectangle of the screen measured from its top-left, or `None` for all of it.
+fn configure_ns_window(inset: Option<CGRect>) {
     unsafe {
         let app: Retained<AnyObject> = msg_send_id![
             AnyClass::get("NSApplication").unwrap(),
@@ -55,10 +78,15 @@ fn configure_ns_window() {
         // Borderless (NSWindowStyleMaskBorderless = 0)
         let _: () = msg_send![&*ns_win, setStyleMask: 0_u64];
 
-        // Cover full screen
+        // Cover the screen, or the region of it with Cocoa's bottom-left origin
         let Some(screen): Option<Retained<AnyObject>> = msg_send_id![&*ns_win, screen]
         else { return };
-        let frame: CGRect = msg_send![&*screen, frame];
+        let mut frame: CGRect = msg_send![&*screen, frame];
+        if let Some(r) = inset {
+            frame.origin.x += r.origin.x;
+            frame.origin.y += frame.size.height - r.origin.y - r.size.height;
+            frame.size = r.size;
+        }
         let _: () = msg_send![&*ns_win, setFrame: frame display: Bool::YES];
     }
 }
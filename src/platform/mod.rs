@@ -1,9 +1,11 @@
 #[cfg(target_os = "linux")]
 mod linux;
+#[cfg(target_os = "linux")]
+mod x11;
 #[cfg(target_os = "macos")]
 mod macos;
 
 #[cfg(target_os = "linux")]
-pub use linux::setup_window;
+pub use linux::{cursor_position, init, setup_window};
 #[cfg(target_os = "macos")]
-pub use macos::setup_window;
+pub use macos::{cursor_position, init, setup_window};
@@ -0,0 +1,120 @@
+//! Overlay windows for X11 sessions and compositors without wlr-layer-shell. GTK 4 no
+//! longer exposes override-redirect, so the X window is configured through Xlib, loaded
+//! at runtime like the GL entry points so builds without the X11 backend still link.
+
+use gtk4::prelude::*;
+use gtk4::gdk;
+use std::ffi::{c_int, c_long, c_uint, c_ulong, c_void, CStr};
+
+type XDisplay = c_void;
+type XWindow = c_ulong;
+
+/// `XSetWindowAttributes` from Xlib.h
+#[repr(C)]
+#[derive(Default)]
+struct SetWindowAttributes {
+    background_pixmap: c_ulong,
+    background_pixel: c_ulong,
+    border_pixmap: c_ulong,
+    border_pixel: c_ulong,
+    bit_gravity: c_int,
+    win_gravity: c_int,
+    backing_store: c_int,
+    backing_planes: c_ulong,
+    backing_pixel: c_ulong,
+    save_under: c_int,
+    event_mask: c_long,
+    do_not_propagate_mask: c_long,
+    override_redirect: c_int,
+    colormap: c_ulong,
+    cursor: c_ulong,
+}
+
+/// `CWOverrideRedirect` attribute mask bit
+const CW_OVERRIDE_REDIRECT: c_ulong = 1 << 9;
+
+struct Xlib {
+    get_xdisplay: unsafe extern "C" fn(*mut c_void) -> *mut XDisplay,
+    get_xid: unsafe extern "C" fn(*mut c_void) -> XWindow,
+    change_attributes: unsafe extern "C" fn(*mut XDisplay, XWindow, c_ulong, *mut SetWindowAttributes) -> c_int,
+    move_resize: unsafe extern "C" fn(*mut XDisplay, XWindow, c_int, c_int, c_uint, c_uint) -> c_int,
+    raise: unsafe extern "C" fn(*mut XDisplay, XWindow) -> c_int,
+    flush: unsafe extern "C" fn(*mut XDisplay) -> c_int,
+}
+
+/// Looks up `name` in `lib` as a function pointer of type `F`, which must match its C signature.
+unsafe fn symbol<F>(lib: *mut c_void, name: &CStr) -> F {
+    let ptr = libc::dlsym(lib, name.as_ptr());
+    if ptr.is_null() {
+        eprintln!("x11 overlay: missing symbol {}", name.to_string_lossy());
+        std::process::exit(1);
+    }
+    std::mem::transmute_copy(&ptr)
+}
+
+impl Xlib {
+    fn load() -> Self {
+        let open = |name: &CStr| {
+            let lib = unsafe { libc::dlopen(name.as_ptr(), libc::RTLD_LAZY) };
+            if lib.is_null() {
+                eprintln!("x11 overlay: failed to load {}", name.to_string_lossy());
+                std::process::exit(1);
+            }
+            lib
+        };
+        let (gtk, x11) = (open(c"libgtk-4.so.1"), open(c"libX11.so.6"));
+        unsafe {
+            Self {
+                get_xdisplay: symbol(gtk, c"gdk_x11_display_get_xdisplay"),
+                get_xid: symbol(gtk, c"gdk_x11_surface_get_xid"),
+                change_attributes: symbol(x11, c"XChangeWindowAttributes"),
+                move_resize: symbol(x11, c"XMoveResizeWindow"),
+                raise: symbol(x11, c"XRaiseWindow"),
+                flush: symbol(x11, c"XFlush"),
+            }
+        }
+    }
+
+    fn handles(&self, win: &gtk4::Window) -> Option<(*mut XDisplay, XWindow)> {
+        let surface = win.surface()?;
+        unsafe {
+            let display = (self.get_xdisplay)(surface.display().as_ptr().cast());
+            let xid = (self.get_xid)(surface.as_ptr().cast());
+            Some((display, xid))
+        }
+    }
+}
+
+pub fn is_x11(display: &gdk::Display) -> bool {
+    display.type_().name() == "GdkX11Display"
+}
+
+/// Makes `win` an undecorated, always-on-top overlay covering `area` of the desktop
+/// layout that the window manager leaves alone. Transparency needs a compositing
+/// manager, which gives GTK an ARGB visual; clicks pass through the input region
+/// set by the caller.
+pub fn setup_window(win: &gtk4::Window, area: &gdk::Rectangle) {
+    win.set_decorated(false);
+    win.set_default_size(area.width(), area.height());
+    let xlib = std::rc::Rc::new(Xlib::load());
+
+    // Override-redirect only takes effect when set before the window is first mapped
+    let lib = xlib.clone();
+    win.connect_realize(move |w| {
+        let Some((display, xid)) = lib.handles(w) else { return };
+        let mut attrs = SetWindowAttributes { override_redirect: 1, ..Default::default() };
+        unsafe { (lib.change_attributes)(display, xid, CW_OVERRIDE_REDIRECT, &mut attrs) };
+    });
+
+    // Unmanaged windows stay where they're put, so place and raise them ourselves
+    let (x, y, w, h) = (area.x(), area.y(), area.width(), area.height());
+    win.connect_map(move |win| {
+        let Some((display, xid)) = xlib.handles(win) else { return };
+        let k = win.scale_factor();
+        unsafe {
+            (xlib.move_resize)(display, xid, x * k, y * k, (w * k) as c_uint, (h * k) as c_uint);
+            (xlib.raise)(display, xid);
+            (xlib.flush)(display);
+        }
+    });
+}
@@ -0,0 +1,337 @@
+use crate::config::settings;
+use crate::shapes::{self, Atlas, Glyphs, Paths, Seg};
+use crate::types::Shape;
+use gtk4::prelude::*;
+use gtk4::{gdk, gsk};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Draw commands the simulation emits for each frame. Every shape is drawn centered on the
+/// current origin, so a particle is a `save`, a few transforms, its commands and a `restore`.
+pub trait Renderer {
+    fn save(&mut self);
+    fn restore(&mut self);
+    fn translate(&mut self, x: f32, y: f32);
+    fn rotate(&mut self, radians: f32);
+    fn scale(&mut self, sx: f32, sy: f32);
+    /// Fills `shape` stretched to `w` x `h`
+    fn shape(&mut self, shape: Shape, w: f32, h: f32, c: &[f32; 3], alpha: f32);
+    /// Fills an outline given in absolute coordinates
+    fn path(&mut self, segs: &[Seg], c: &[f32; 3], alpha: f32);
+    /// Draws glyph `idx` in `c` stretched to `w` x `h` font sizes
+    fn glyph(&mut self, idx: u32, w: f32, h: f32, c: &[f32; 3], alpha: f32);
+    /// Draws sprite texture `idx` `h` pixels tall, squashed horizontally by `flip`
+    fn sprite(&mut self, idx: u32, h: f32, flip: f32, c: &[f32; 3], alpha: f32);
+    /// Soft radial halo of radius `r`
+    fn glow(&mut self, c: &[f32; 3], alpha: f32, r: f32);
+    /// Tapered streak trailing behind the origin along -x, fading out towards its tail
+    fn streak(&mut self, c: &[f32; 3], alpha: f32, len: f32, width: f32);
+    /// Metallic disc `w` x `h` shaded by `shade`, with a highlight band at `highlight` across it
+    fn coin(&mut self, w: f32, h: f32, c: &[f32; 3], alpha: f32, shade: f32, highlight: f32);
+}
+
+/// Sprite index and quantized tint
+type SpriteKey = (u32, [u8; 3]);
+
+/// Resources an effect draws from, shared by every backend.
+pub struct Assets {
+    paths: Paths,
+    glyphs: Glyphs,
+    sprites: Vec<gdk::Texture>,
+    // Pre-rendered shape and glyph textures, filled in lazily by the snapshot backend
+    atlas: RefCell<Atlas>,
+    // Sprite pixels for the cairo backend by sprite and quantized tint, copied on first use
+    sprite_surfaces: RefCell<HashMap<SpriteKey, Option<cairo::ImageSurface>>>,
+}
+
+impl Assets {
+    pub fn new(glyphs: Glyphs, sprites: Vec<gdk::Texture>) -> Self {
+        Self {
+            paths: Paths::new(),
+            glyphs,
+            atlas: RefCell::new(Atlas::new()),
+            sprite_surfaces: RefCell::new(HashMap::new()),
+            sprites,
+        }
+    }
+}
+
+/// Streak layers as (length, width) fractions: stacked gradients of decreasing length and
+/// increasing width give the taper.
+const STREAK_LAYERS: [(f32, f32); 3] = [(1.0, 0.35), (0.6, 0.7), (0.3, 1.0)];
+
+/// Color stops across a coin face: darkened rims around a lightened highlight band.
+fn coin_stops(c: &[f32; 3], shade: f32, highlight: f32) -> [(f32, [f32; 3]); 3] {
+    let tone = |f: f32, lift: f32| c.map(|v| (v + (1.0 - v) * lift) * f);
+    [(0.0, tone(shade * 0.55, 0.0)), (highlight, tone(shade, 0.55)), (1.0, tone(shade * 0.55, 0.0))]
+}
+
+/// Copies a texture into a cairo surface, multiplying its color channels by `tint` the way
+/// the snapshot backend's color matrix does. GDK's default download format matches ARGB32.
+fn texture_surface(tex: &gdk::Texture, tint: [f32; 3]) -> Option<cairo::ImageSurface> {
+    let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, tex.width(), tex.height()).ok()?;
+    let stride = surface.stride() as usize;
+    {
+        let mut data = surface.data().ok()?;
+        tex.download(&mut data, stride);
+        if tint != [1.0; 3] {
+            let [r, g, b] = tint;
+            // Premultiplied pixels, so scaling the color channels leaves alpha alone
+            for px in data.chunks_exact_mut(4) {
+                let v = u32::from_ne_bytes([px[0], px[1], px[2], px[3]]);
+                let ch = |shift: u32, f: f32| ((((v >> shift) & 0xff) as f32 * f).round() as u32) << shift;
+                let v = (v & 0xff00_0000) | ch(16, r) | ch(8, g) | ch(0, b);
+                px.copy_from_slice(&v.to_ne_bytes());
+            }
+        }
+    }
+    surface.mark_dirty();
+    Some(surface)
+}
+
+/// Builds GSK render nodes. Shapes and glyphs come from the texture atlas, so each costs
+/// about as much as a plain rect. Outlines are collected and painted by `finish` into a
+/// single cairo node, on top of everything else drawn through this renderer.
+pub struct SnapshotRenderer<'a> {
+    snap: &'a gtk4::Snapshot,
+    assets: &'a Assets,
+    outlines: Vec<(Vec<Seg>, [f32; 3], f32)>,
+}
+
+impl<'a> SnapshotRenderer<'a> {
+    pub fn new(snap: &'a gtk4::Snapshot, assets: &'a Assets) -> Self {
+        Self { snap, assets, outlines: Vec::new() }
+    }
+
+    /// Rasterizes the collected outlines. Streamer outlines change every frame, so they
+    /// can't come from the atlas, and one surface for all of them is far cheaper than one
+    /// per particle.
+    pub fn finish(self) {
+        let Some(area) = self.outlines.iter()
+            .map(|(segs, _, _)| shapes::bounds(segs))
+            .reduce(|a, b| a.union(&b)) else { return };
+        let cr = self.snap.append_cairo(&area);
+        for (segs, c, alpha) in &self.outlines {
+            shapes::trace(&cr, segs);
+            cr.set_source_rgba(c[0] as f64, c[1] as f64, c[2] as f64, *alpha as f64);
+            let _ = cr.fill();
+        }
+    }
+
+    /// Draws an atlas texture whose unit-space bounds are `b`, stretched to `w` x `h`.
+    fn texture(&self, tex: &gdk::Texture, b: &graphene::Rect, w: f32, h: f32, alpha: f32) {
+        let rect = graphene::Rect::new(b.x() * w, b.y() * h, b.width() * w, b.height() * h);
+        if alpha < 1.0 { self.snap.push_opacity(alpha as f64); }
+        self.snap.append_texture(tex, &rect);
+        if alpha < 1.0 { self.snap.pop(); }
+    }
+}
+
+impl Renderer for SnapshotRenderer<'_> {
+    fn save(&mut self) { self.snap.save(); }
+    fn restore(&mut self) { self.snap.restore(); }
+    fn translate(&mut self, x: f32, y: f32) { self.snap.translate(&graphene::Point::new(x, y)); }
+    fn rotate(&mut self, radians: f32) { self.snap.rotate(radians.to_degrees()); }
+    fn scale(&mut self, sx: f32, sy: f32) { self.snap.scale(sx, sy); }
+
+    fn shape(&mut self, shape: Shape, w: f32, h: f32, c: &[f32; 3], alpha: f32) {
+        if matches!(shape, Shape::Rect | Shape::Mixed | Shape::Streamer) {
+            let rgba = gdk::RGBA::new(c[0], c[1], c[2], alpha);
+            self.snap.append_color(&rgba, &graphene::Rect::new(-w / 2.0, -h / 2.0, w, h));
+            return;
+        }
+        let mut atlas = self.assets.atlas.borrow_mut();
+        let Some((tex, b)) = atlas.shape(&self.assets.paths, shape, w.abs().max(h), c) else { return };
+        self.texture(tex, b, w, h, alpha);
+    }
+
+    fn path(&mut self, segs: &[Seg], c: &[f32; 3], alpha: f32) {
+        if !segs.is_empty() { self.outlines.push((segs.to_vec(), *c, alpha)); }
+    }
+
+    fn glyph(&mut self, idx: u32, w: f32, h: f32, c: &[f32; 3], alpha: f32) {
+        let mut atlas = self.assets.atlas.borrow_mut();
+        let Some((tex, b)) = atlas.glyph(&self.assets.glyphs, idx, w.abs().max(h), c) else { return };
+        self.texture(tex, b, w, h, alpha);
+    }
+
+    fn sprite(&mut self, idx: u32, h: f32, flip: f32, c: &[f32; 3], alpha: f32) {
+        let tex = &self.assets.sprites[idx as usize];
+        let w = h * tex.width() as f32 / tex.height().max(1) as f32 * flip;
+        let rect = graphene::Rect::new(-w / 2.0, -h / 2.0, w, h);
+        if settings().tint {
+            // Multiply the sprite's channels by the particle color, folding in the fade
+            let m = graphene::Matrix::from_float([
+                c[0], 0.0, 0.0, 0.0,
+                0.0, c[1], 0.0, 0.0,
+                0.0, 0.0, c[2], 0.0,
+                0.0, 0.0, 0.0, alpha,
+            ]);
+            self.snap.push_color_matrix(&m, &graphene::Vec4::zero());
+            self.snap.append_texture(tex, &rect);
+            self.snap.pop();
+        } else {
+            if alpha < 1.0 { self.snap.push_opacity(alpha as f64); }
+            self.snap.append_texture(tex, &rect);
+            if alpha < 1.0 { self.snap.pop(); }
+        }
+    }
+
+    fn glow(&mut self, c: &[f32; 3], alpha: f32, r: f32) {
+        let stops = [
+            gsk::ColorStop::new(0.0, gdk::RGBA::new(c[0], c[1], c[2], alpha * 0.45)),
+            gsk::ColorStop::new(1.0, gdk::RGBA::new(c[0], c[1], c[2], 0.0)),
+        ];
+        self.snap.append_radial_gradient(
+            &graphene::Rect::new(-r, -r, r * 2.0, r * 2.0),
+            &graphene::Point::new(0.0, 0.0),
+            r, r, 0.0, 1.0,
+            &stops,
+        );
+    }
+
+    fn streak(&mut self, c: &[f32; 3], alpha: f32, len: f32, width: f32) {
+        for (lf, wf) in STREAK_LAYERS {
+            let (l, w) = (len * lf, width * wf);
+            let stops = [
+                gsk::ColorStop::new(0.0, gdk::RGBA::new(c[0], c[1], c[2], 0.0)),
+                gsk::ColorStop::new(1.0, gdk::RGBA::new(c[0], c[1], c[2], alpha * 0.6)),
+            ];
+            self.snap.append_linear_gradient(
+                &graphene::Rect::new(-l, -w / 2.0, l, w),
+                &graphene::Point::new(-l, 0.0),
+                &graphene::Point::new(0.0, 0.0),
+                &stops,
+            );
+        }
+    }
+
+    fn coin(&mut self, w: f32, h: f32, c: &[f32; 3], alpha: f32, shade: f32, highlight: f32) {
+        let stops = coin_stops(c, shade, highlight)
+            .map(|(at, c)| gsk::ColorStop::new(at, gdk::RGBA::new(c[0], c[1], c[2], alpha)));
+        let rect = graphene::Rect::new(-w / 2.0, -h / 2.0, w, h);
+        let corner = graphene::Size::new(w / 2.0, h / 2.0);
+        self.snap.push_rounded_clip(&gsk::RoundedRect::new(rect, corner, corner, corner, corner));
+        self.snap.append_linear_gradient(
+            &rect,
+            &graphene::Point::new(-w / 2.0, 0.0),
+            &graphene::Point::new(w / 2.0, 0.0),
+            &stops,
+        );
+        self.snap.pop();
+    }
+}
+
+/// Paints with cairo, onto an image surface or a GSK cairo node. The caller's operator is
+/// kept, which is how additive and screen blending are done.
+pub struct CairoRenderer<'a> {
+    cr: &'a cairo::Context,
+    assets: &'a Assets,
+}
+
+impl<'a> CairoRenderer<'a> {
+    pub fn new(cr: &'a cairo::Context, assets: &'a Assets) -> Self {
+        Self { cr, assets }
+    }
+
+    fn fill(&self, c: &[f32; 3], alpha: f32) {
+        self.cr.set_source_rgba(c[0] as f64, c[1] as f64, c[2] as f64, alpha as f64);
+        let _ = self.cr.fill();
+    }
+}
+
+impl Renderer for CairoRenderer<'_> {
+    fn save(&mut self) { let _ = self.cr.save(); }
+    fn restore(&mut self) { let _ = self.cr.restore(); }
+    fn translate(&mut self, x: f32, y: f32) { self.cr.translate(x as f64, y as f64); }
+    fn rotate(&mut self, radians: f32) { self.cr.rotate(radians as f64); }
+    fn scale(&mut self, sx: f32, sy: f32) { self.cr.scale(sx as f64, sy as f64); }
+
+    fn shape(&mut self, shape: Shape, w: f32, h: f32, c: &[f32; 3], alpha: f32) {
+        let (w, h) = (w as f64, h as f64);
+        match shape {
+            Shape::Rect | Shape::Mixed | Shape::Streamer => self.cr.rectangle(-w / 2.0, -h / 2.0, w, h),
+            Shape::Circle => {
+                let _ = self.cr.save();
+                self.cr.scale(w, h);
+                self.cr.arc(0.0, 0.0, 0.5, 0.0, std::f64::consts::TAU);
+                let _ = self.cr.restore();
+            }
+            shape => {
+                let Some(outline) = self.assets.paths.outline(shape) else { return };
+                let _ = self.cr.save();
+                self.cr.scale(w, h);
+                shapes::trace(self.cr, outline);
+                let _ = self.cr.restore();
+            }
+        }
+        self.fill(c, alpha);
+    }
+
+    fn path(&mut self, segs: &[Seg], c: &[f32; 3], alpha: f32) {
+        shapes::trace(self.cr, segs);
+        self.fill(c, alpha);
+    }
+
+    fn glyph(&mut self, idx: u32, w: f32, h: f32, c: &[f32; 3], alpha: f32) {
+        let _ = self.cr.save();
+        self.cr.scale((w / Glyphs::SIZE) as f64, (h / Glyphs::SIZE) as f64);
+        self.cr.push_group();
+        self.assets.glyphs.node(idx, c).draw(self.cr);
+        let _ = self.cr.pop_group_to_source();
+        let _ = self.cr.paint_with_alpha(alpha as f64);
+        let _ = self.cr.restore();
+    }
+
+    fn sprite(&mut self, idx: u32, h: f32, flip: f32, c: &[f32; 3], alpha: f32) {
+        let tint = shapes::quantize(if settings().tint { c } else { &[1.0; 3] });
+        let mut surfaces = self.assets.sprite_surfaces.borrow_mut();
+        let surf = surfaces.entry((idx, tint)).or_insert_with(|| {
+            texture_surface(&self.assets.sprites[idx as usize], shapes::unquantize(tint))
+        });
+        let Some(surf) = surf.as_ref() else { return };
+        let (tw, th) = (surf.width() as f64, surf.height().max(1) as f64);
+        let _ = self.cr.save();
+        self.cr.scale(h as f64 / th * flip as f64, h as f64 / th);
+        let _ = self.cr.set_source_surface(surf, -tw / 2.0, -th / 2.0);
+        let _ = self.cr.paint_with_alpha(alpha as f64);
+        let _ = self.cr.restore();
+    }
+
+    fn glow(&mut self, c: &[f32; 3], alpha: f32, r: f32) {
+        let (red, green, blue, r) = (c[0] as f64, c[1] as f64, c[2] as f64, r as f64);
+        let grad = cairo::RadialGradient::new(0.0, 0.0, 0.0, 0.0, 0.0, r);
+        grad.add_color_stop_rgba(0.0, red, green, blue, alpha as f64 * 0.45);
+        grad.add_color_stop_rgba(1.0, red, green, blue, 0.0);
+        self.cr.arc(0.0, 0.0, r, 0.0, std::f64::consts::TAU);
+        let _ = self.cr.set_source(&grad);
+        let _ = self.cr.fill();
+    }
+
+    fn streak(&mut self, c: &[f32; 3], alpha: f32, len: f32, width: f32) {
+        let (r, g, b) = (c[0] as f64, c[1] as f64, c[2] as f64);
+        for (lf, wf) in STREAK_LAYERS {
+            let (l, w) = ((len * lf) as f64, (width * wf) as f64);
+            let grad = cairo::LinearGradient::new(-l, 0.0, 0.0, 0.0);
+            grad.add_color_stop_rgba(0.0, r, g, b, 0.0);
+            grad.add_color_stop_rgba(1.0, r, g, b, alpha as f64 * 0.6);
+            self.cr.rectangle(-l, -w / 2.0, l, w);
+            let _ = self.cr.set_source(&grad);
+            let _ = self.cr.fill();
+        }
+    }
+
+    fn coin(&mut self, w: f32, h: f32, c: &[f32; 3], alpha: f32, shade: f32, highlight: f32) {
+        let w = w as f64;
+        let grad = cairo::LinearGradient::new(-w / 2.0, 0.0, w / 2.0, 0.0);
+        for (at, c) in coin_stops(c, shade, highlight) {
+            grad.add_color_stop_rgba(at as f64, c[0] as f64, c[1] as f64, c[2] as f64, alpha as f64);
+        }
+        let _ = self.cr.save();
+        self.cr.scale(w, h as f64);
+        self.cr.arc(0.0, 0.0, 0.5, 0.0, std::f64::consts::TAU);
+        let _ = self.cr.restore();
+        let _ = self.cr.set_source(&grad);
+        let _ = self.cr.fill();
+    }
+}
@@ -0,0 +1,301 @@
+use crate::config::settings;
+use crate::types::Shape;
+use gtk4::prelude::*;
+use gtk4::{gdk, glib, gsk, pango};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// One outline command in unit space: shapes fit the box from -0.5 to 0.5 on both axes.
+#[derive(Clone, Copy)]
+pub enum Seg {
+    Move(f32, f32),
+    Line(f32, f32),
+    Cubic(f32, f32, f32, f32, f32, f32),
+    Close,
+}
+
+/// Outline of a path-drawn shape, or `None` for shapes drawn directly as rects and clips.
+pub fn outline(shape: Shape) -> Option<Vec<Seg>> {
+    use Seg::*;
+    let segs = match shape {
+        Shape::Star => {
+            let mut v = Vec::with_capacity(11);
+            for k in 0..10 {
+                let ang = -std::f32::consts::FRAC_PI_2 + k as f32 * std::f32::consts::PI / 5.0;
+                let rad = if k % 2 == 0 { 0.5 } else { 0.2 };
+                let (x, y) = (ang.cos() * rad, ang.sin() * rad);
+                v.push(if k == 0 { Move(x, y) } else { Line(x, y) });
+            }
+            v.push(Close);
+            v
+        }
+        Shape::Heart => vec![
+            Move(0.0, -0.2),
+            Cubic(0.0, -0.45, -0.5, -0.45, -0.5, -0.15),
+            Cubic(-0.5, 0.1, -0.15, 0.25, 0.0, 0.45),
+            Cubic(0.15, 0.25, 0.5, 0.1, 0.5, -0.15),
+            Cubic(0.5, -0.45, 0.0, -0.45, 0.0, -0.2),
+            Close,
+        ],
+        Shape::Triangle => vec![Move(0.0, -0.5), Line(0.5, 0.4), Line(-0.5, 0.4), Close],
+        Shape::Diamond => vec![Move(0.0, -0.5), Line(0.35, 0.0), Line(0.0, 0.5), Line(-0.35, 0.0), Close],
+        // S-curved strip: down the left edge, across the bottom, back up the right edge
+        Shape::Ribbon => vec![
+            Move(-0.35, -0.5),
+            Cubic(0.15, -0.2, -0.45, 0.2, 0.05, 0.5),
+            Line(0.35, 0.5),
+            Cubic(-0.15, 0.2, 0.45, -0.2, -0.05, -0.5),
+            Close,
+        ],
+        Shape::Rect | Shape::Circle | Shape::Mixed | Shape::Glyph | Shape::Sprite
+            | Shape::Streamer => return None,
+    };
+    Some(segs)
+}
+
+/// Box around every point of an outline, control points included, grown by a pixel
+/// for antialiasing.
+pub fn bounds(segs: &[Seg]) -> graphene::Rect {
+    let (mut x0, mut y0, mut x1, mut y1) = (f32::MAX, f32::MAX, f32::MIN, f32::MIN);
+    let mut add = |x: f32, y: f32| {
+        (x0, y0, x1, y1) = (x0.min(x), y0.min(y), x1.max(x), y1.max(y));
+    };
+    for &seg in segs {
+        match seg {
+            Seg::Move(x, y) | Seg::Line(x, y) => add(x, y),
+            Seg::Cubic(x1, y1, x2, y2, x3, y3) => {
+                add(x1, y1);
+                add(x2, y2);
+                add(x3, y3);
+            }
+            Seg::Close => {}
+        }
+    }
+    if x0 > x1 { return graphene::Rect::zero(); }
+    graphene::Rect::new(x0 - 1.0, y0 - 1.0, x1 - x0 + 2.0, y1 - y0 + 2.0)
+}
+
+/// Replays outline commands as the current path of a cairo context.
+pub fn trace(cr: &cairo::Context, segs: &[Seg]) {
+    cr.new_path();
+    for &seg in segs {
+        match seg {
+            Seg::Move(x, y) => cr.move_to(x as f64, y as f64),
+            Seg::Line(x, y) => cr.line_to(x as f64, y as f64),
+            Seg::Cubic(x1, y1, x2, y2, x3, y3) => cr.curve_to(
+                x1 as f64, y1 as f64, x2 as f64, y2 as f64, x3 as f64, y3 as f64,
+            ),
+            Seg::Close => cr.close_path(),
+        }
+    }
+}
+
+/// Closed outline along `left` and back along `right`, smoothed with curves through the
+/// midpoints of each edge. Used for ribbons whose width varies along their length.
+pub fn strip(left: &[(f32, f32)], right: &[(f32, f32)]) -> Vec<Seg> {
+    let mut segs = Vec::with_capacity(left.len() * 2 + 3);
+    smooth_edge(&mut segs, left, true);
+    let back: Vec<_> = right.iter().rev().copied().collect();
+    smooth_edge(&mut segs, &back, false);
+    segs.push(Seg::Close);
+    segs
+}
+
+fn smooth_edge(segs: &mut Vec<Seg>, pts: &[(f32, f32)], first: bool) {
+    let mut cur = pts[0];
+    segs.push(if first { Seg::Move(cur.0, cur.1) } else { Seg::Line(cur.0, cur.1) });
+    for k in 1..pts.len() - 1 {
+        // Quadratic through the point to the next midpoint, raised to a cubic
+        let q = pts[k];
+        let end = ((q.0 + pts[k + 1].0) / 2.0, (q.1 + pts[k + 1].1) / 2.0);
+        segs.push(Seg::Cubic(
+            cur.0 + (q.0 - cur.0) * 2.0 / 3.0, cur.1 + (q.1 - cur.1) * 2.0 / 3.0,
+            end.0 + (q.0 - end.0) * 2.0 / 3.0, end.1 + (q.1 - end.1) * 2.0 / 3.0,
+            end.0, end.1,
+        ));
+        cur = end;
+    }
+    let last = pts[pts.len() - 1];
+    segs.push(Seg::Line(last.0, last.1));
+}
+
+/// Outlines for every path-drawn shape, built once and scaled per particle at draw time.
+pub struct Paths {
+    paths: Vec<(Shape, Vec<Seg>)>,
+}
+
+impl Paths {
+    pub fn new() -> Self {
+        let paths = [Shape::Star, Shape::Heart, Shape::Triangle, Shape::Diamond, Shape::Ribbon]
+            .into_iter()
+            .filter_map(|shape| Some((shape, outline(shape)?)))
+            .collect();
+        Self { paths }
+    }
+
+    pub fn outline(&self, shape: Shape) -> Option<&[Seg]> {
+        self.paths.iter().find(|(s, _)| *s == shape).map(|(_, o)| o.as_slice())
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+enum AtlasKey {
+    Shape(Shape, u32, [u8; 3]),
+    Glyph(u32, u32, [u8; 3]),
+}
+
+/// Steps per channel in cache keys. A fixed palette only has a few colors, so they're
+/// kept exact to match the rects, which aren't cached; five bits keeps gradients and
+/// hue cycling from flooding the caches.
+fn levels() -> f32 {
+    let s = settings();
+    if s.gradient.is_empty() && s.rainbow.is_none() { 255.0 } else { 31.0 }
+}
+
+pub fn quantize(color: &[f32; 3]) -> [u8; 3] {
+    let levels = levels();
+    color.map(|c| (c.clamp(0.0, 1.0) * levels).round() as u8)
+}
+
+pub fn unquantize(q: [u8; 3]) -> [f32; 3] {
+    let levels = levels();
+    q.map(|v| v as f32 / levels)
+}
+
+/// Shapes and glyphs pre-rendered into textures once per size bucket and color, so every
+/// particle draws as a single texture node instead of a clip or path fill. GSK has no
+/// sub-texture node, so each entry is its own small texture; the renderer batches them.
+pub struct Atlas {
+    entries: HashMap<AtlasKey, (gdk::Texture, graphene::Rect)>,
+}
+
+impl Atlas {
+    pub fn new() -> Self {
+        Self { entries: HashMap::new() }
+    }
+
+    /// Texture for `shape` in `color` drawn `size` pixels across, with the bounds it covers
+    /// in unit space. `None` for shapes without an outline.
+    pub fn shape(&mut self, paths: &Paths, shape: Shape, size: f32, color: &[f32; 3])
+        -> Option<&(gdk::Texture, graphene::Rect)>
+    {
+        let q = quantize(color);
+        let px = bucket(size);
+        let key = AtlasKey::Shape(shape, px, q);
+        if !self.entries.contains_key(&key) {
+            let outline = paths.outline(shape);
+            if shape != Shape::Circle && outline.is_none() { return None; }
+            // A pixel of padding on every side keeps the antialiased edges inside the texture
+            let pad = 1.0 / (px - 2) as f32;
+            let bounds = graphene::Rect::new(-0.5 - pad, -0.5 - pad, 1.0 + pad * 2.0, 1.0 + pad * 2.0);
+            let tex = render(px, px, |cr| {
+                cr.translate(px as f64 / 2.0, px as f64 / 2.0);
+                cr.scale((px - 2) as f64, (px - 2) as f64);
+                match outline {
+                    Some(segs) => trace(cr, segs),
+                    None => cr.arc(0.0, 0.0, 0.5, 0.0, std::f64::consts::TAU),
+                }
+                let c = unquantize(q);
+                cr.set_source_rgb(c[0] as f64, c[1] as f64, c[2] as f64);
+                let _ = cr.fill();
+            })?;
+            self.entries.insert(AtlasKey::Shape(shape, px, q), (tex, bounds));
+        }
+        self.entries.get(&key)
+    }
+
+    /// Texture for glyph `idx` in `color` drawn `size` pixels across, with its bounds in
+    /// units of `Glyphs::SIZE`.
+    pub fn glyph(&mut self, glyphs: &Glyphs, idx: u32, size: f32, color: &[f32; 3])
+        -> Option<&(gdk::Texture, graphene::Rect)>
+    {
+        let q = quantize(color);
+        let px = bucket(size);
+        let key = AtlasKey::Glyph(idx, px, q);
+        if !self.entries.contains_key(&key) {
+            let node = glyphs.node(idx, &unquantize(q));
+            let b = node.bounds();
+            if b.width() <= 0.0 || b.height() <= 0.0 { return None; }
+            // `size` spans the font size, so scale the node's bounds to match
+            let k = px as f32 / Glyphs::SIZE;
+            let (tw, th) = ((b.width() * k).ceil() as u32, (b.height() * k).ceil() as u32);
+            let tex = render(tw.max(1), th.max(1), |cr| {
+                cr.scale(k as f64, k as f64);
+                cr.translate(-b.x() as f64, -b.y() as f64);
+                node.draw(cr);
+            })?;
+            let s = Glyphs::SIZE;
+            let bounds = graphene::Rect::new(b.x() / s, b.y() / s, b.width() / s, b.height() / s);
+            self.entries.insert(AtlasKey::Glyph(idx, px, q), (tex, bounds));
+        }
+        self.entries.get(&key)
+    }
+}
+
+/// Texture size for drawing `size` pixels across: oversampled for HiDPI and rounded up to
+/// a power of two so nearby sizes share an entry.
+fn bucket(size: f32) -> u32 {
+    ((size.abs() * 2.0).ceil() as u32).clamp(8, 512).next_power_of_two()
+}
+
+/// Paints `draw` into a transparent `w` x `h` surface and wraps the pixels as a texture.
+fn render(w: u32, h: u32, draw: impl FnOnce(&cairo::Context)) -> Option<gdk::Texture> {
+    let mut surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w as i32, h as i32).ok()?;
+    {
+        let cr = cairo::Context::new(&surface).ok()?;
+        draw(&cr);
+    }
+    surface.flush();
+    let stride = surface.stride() as usize;
+    let bytes = glib::Bytes::from(&*surface.data().ok()?);
+    // ARGB32 is native-endian premultiplied
+    #[cfg(target_endian = "little")]
+    let format = gdk::MemoryFormat::B8g8r8a8Premultiplied;
+    #[cfg(target_endian = "big")]
+    let format = gdk::MemoryFormat::A8r8g8b8Premultiplied;
+    Some(gdk::MemoryTexture::new(w as i32, h as i32, format, &bytes, stride).upcast())
+}
+
+/// Text and emoji glyphs rendered through Pango once per glyph and quantized color, then
+/// reused as render nodes. Color emoji keep their own colors; plain glyphs take the particle's.
+pub struct Glyphs {
+    layouts: Vec<pango::Layout>,
+    nodes: RefCell<HashMap<(u32, [u8; 3]), gsk::RenderNode>>,
+}
+
+impl Glyphs {
+    /// Font size the glyphs are laid out at; particles scale the cached nodes from this.
+    pub const SIZE: f32 = 64.0;
+
+    pub fn new(ctx: &pango::Context, glyphs: &[String]) -> Self {
+        let mut desc = pango::FontDescription::from_string("Sans");
+        desc.set_absolute_size(Self::SIZE as f64 * pango::SCALE as f64);
+        let layouts = glyphs.iter()
+            .map(|g| {
+                let layout = pango::Layout::new(ctx);
+                layout.set_font_description(Some(&desc));
+                layout.set_text(g);
+                layout
+            })
+            .collect();
+        Self { layouts, nodes: RefCell::new(HashMap::new()) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.layouts.len()
+    }
+
+    /// Node drawing glyph `idx` in `color` centered on the origin, rendered on first use.
+    pub fn node(&self, idx: u32, color: &[f32; 3]) -> gsk::RenderNode {
+        let q = quantize(color);
+        self.nodes.borrow_mut().entry((idx, q)).or_insert_with(|| {
+            let layout = &self.layouts[idx as usize];
+            let (tw, th) = layout.pixel_size();
+            let c = unquantize(q);
+            let snap = gtk4::Snapshot::new();
+            snap.translate(&graphene::Point::new(-tw as f32 / 2.0, -th as f32 / 2.0));
+            snap.append_layout(layout, &gdk::RGBA::new(c[0], c[1], c[2], 1.0));
+            snap.to_node().unwrap_or_else(|| gsk::ContainerNode::new(&[]).upcast())
+        }).clone()
+    }
+}
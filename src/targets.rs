@@ -0,0 +1,97 @@
+use gtk4::prelude::*;
+use gtk4::{gdk, gdk_pixbuf, pango};
+use rand::Rng;
+use std::path::Path;
+
+/// A resting position particles fly into when forming text or an image.
+pub struct Target {
+    pub x: f64,
+    pub y: f64,
+    /// Color sampled from the source image; text takes the palette instead
+    pub color: Option<[f32; 3]>,
+}
+
+/// Renders `text` with Pango and returns `n` points sampled from the opaque glyph pixels,
+/// scaled and centered to fit a `w` x `h` screen.
+pub fn from_text(ctx: &pango::Context, text: &str, w: f64, h: f64, n: usize) -> Vec<Target> {
+    let layout = pango::Layout::new(ctx);
+    let mut desc = pango::FontDescription::from_string("Sans Bold");
+    desc.set_absolute_size(160.0 * pango::SCALE as f64);
+    layout.set_font_description(Some(&desc));
+    layout.set_text(text);
+    let (tw, th) = layout.pixel_size();
+    if tw <= 0 || th <= 0 { return Vec::new() }
+
+    let snap = gtk4::Snapshot::new();
+    snap.append_layout(&layout, &gdk::RGBA::WHITE);
+    let Some(node) = snap.to_node() else { return Vec::new() };
+    let Ok(mut surface) = cairo::ImageSurface::create(cairo::Format::ARgb32, tw, th) else {
+        return Vec::new();
+    };
+    {
+        let Ok(cr) = cairo::Context::new(&surface) else { return Vec::new() };
+        node.draw(&cr);
+    }
+    let stride = surface.stride() as usize;
+    let Ok(data) = surface.data() else { return Vec::new() };
+
+    let mut opaque = Vec::new();
+    for py in 0..th as usize {
+        for px in 0..tw as usize {
+            // ARGB32 is native-endian, so alpha is the high byte of each pixel
+            let a = u32::from_ne_bytes(data[py * stride + px * 4..][..4].try_into().unwrap()) >> 24;
+            if a > 128 { opaque.push((px, py)); }
+        }
+    }
+    if opaque.is_empty() { return Vec::new() }
+
+    let scale = (w * 0.8 / tw as f64).min(h * 0.35 / th as f64);
+    let ox = (w - tw as f64 * scale) / 2.0;
+    let oy = (h - th as f64 * scale) / 2.0;
+    let mut r = rand::thread_rng();
+    (0..n).map(|_| {
+        let (px, py) = opaque[r.gen_range(0..opaque.len())];
+        Target {
+            x: ox + (px as f64 + r.gen::<f64>()) * scale,
+            y: oy + (py as f64 + r.gen::<f64>()) * scale,
+            color: None,
+        }
+    }).collect()
+}
+
+/// Loads a PNG or SVG (anything gdk-pixbuf can read) scaled to fit the middle of the screen and
+/// returns `n` points sampled from its opaque pixels, each carrying that pixel's color.
+pub fn from_image(path: &Path, w: f64, h: f64, n: usize) -> Vec<Target> {
+    let pixbuf = gdk_pixbuf::Pixbuf::from_file_at_scale(path, (w * 0.6) as i32, (h * 0.6) as i32, true)
+        .unwrap_or_else(|e| {
+            eprintln!("failed to load {}: {e}", path.display());
+            std::process::exit(1);
+        });
+    let (iw, ih) = (pixbuf.width() as usize, pixbuf.height() as usize);
+    let stride = pixbuf.rowstride() as usize;
+    let channels = pixbuf.n_channels() as usize;
+    let has_alpha = pixbuf.has_alpha();
+    let bytes = pixbuf.read_pixel_bytes();
+
+    let mut opaque = Vec::new();
+    for py in 0..ih {
+        for px in 0..iw {
+            let p = &bytes[py * stride + px * channels..][..channels];
+            if has_alpha && p[3] <= 128 { continue; }
+            opaque.push((px, py, [p[0] as f32 / 255.0, p[1] as f32 / 255.0, p[2] as f32 / 255.0]));
+        }
+    }
+    if opaque.is_empty() { return Vec::new() }
+
+    let ox = (w - iw as f64) / 2.0;
+    let oy = (h - ih as f64) / 2.0;
+    let mut r = rand::thread_rng();
+    (0..n).map(|_| {
+        let (px, py, color) = opaque[r.gen_range(0..opaque.len())];
+        Target {
+            x: ox + px as f64 + r.gen::<f64>(),
+            y: oy + py as f64 + r.gen::<f64>(),
+            color: Some(color),
+        }
+    }).collect()
+}
@@ -9,14 +9,126 @@ pub enum AnimType {
     Rain,
     Sparkle,
     Drop,
+    Fountain,
+    Meteors,
+    Vortex,
+    Coins,
 }
 
-#[derive(Clone, Copy, Default, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum Shape {
     #[default]
     Rect,
     Circle,
     Mixed,
+    Star,
+    Heart,
+    Triangle,
+    Diamond,
+    Ribbon,
+    Glyph,
+    Sprite,
+    Streamer,
+}
+
+/// Axis a vortex spins around
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Axis {
+    /// Spiral around the screen center
+    #[default]
+    Center,
+    /// Tornado column rising around a vertical line
+    Vertical,
+}
+
+/// How particles form text or an image
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Form {
+    /// Fly in from the type's launch points, hold, then scatter
+    #[default]
+    Assemble,
+    /// Appear already in formation, hold, then burst outward like `Pop`
+    Burst,
+}
+
+/// How particles composite with each other
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Blend {
+    #[default]
+    Normal,
+    /// Overlapping colors add up, so dense clusters burn towards white
+    Additive,
+    /// Overlaps lighten softly without clipping as quickly as additive
+    Screen,
+}
+
+/// How particles reach the screen
+#[derive(Clone, Copy, Default, PartialEq)]
+pub enum Backend {
+    /// GSK render nodes built per particle every frame
+    #[default]
+    Gsk,
+    /// One instanced GL draw call per frame, shapes drawn by a fragment shader
+    Gl,
+}
+
+/// Which monitors get an overlay window
+#[derive(Clone, PartialEq)]
+pub enum Output {
+    /// Position in GDK's monitor list
+    Index(usize),
+    /// Connector name, e.g. `DP-2`
+    Connector(String),
+    /// One window per monitor
+    All,
+    /// The output with keyboard focus, asked from the compositor
+    Focused,
+    /// The output under the pointer, asked from the compositor
+    Pointer,
+}
+
+impl Default for Output {
+    fn default() -> Self { Self::Index(0) }
+}
+
+/// One coordinate of an emission point
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Coord {
+    /// Fraction of the screen's width or height
+    Frac(f64),
+    /// Pixels from the left or top edge
+    Px(f64),
+}
+
+/// Where bursts, jets and vortices start
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Origin {
+    Point(Coord, Coord),
+    /// Pointer position when the command runs, asked from the compositor
+    Cursor,
+    /// Center of the focused window, asked from the compositor
+    Window,
+}
+
+/// Part of each monitor an effect is confined to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Region {
+    /// Left, top, width and height
+    Rect([Coord; 4]),
+    /// Width and height placed against a side, a corner or the center, given as
+    /// fractions of the room left around the rectangle
+    Anchored([f64; 2], Coord, Coord),
+    /// The focused window, asked from the compositor
+    Window,
+}
+
+/// Hue cycling applied on top of particle colors
+#[derive(Clone, Copy, PartialEq)]
+pub enum Rainbow {
+    /// Each particle's hue rotates over time
+    Time,
+    /// Hue follows horizontal screen position, sweeping over time
+    Position,
 }
 
 pub const DEFAULT_COLORS: [[f32; 3]; 9] = [
@@ -25,8 +137,10 @@ pub const DEFAULT_COLORS: [[f32; 3]; 9] = [
     [0.10, 0.82, 0.82], [1.00, 0.42, 0.70], [0.40, 1.00, 0.40],
 ];
 
+pub const DEFAULT_GLYPHS: &[&str] = &["🎉", "⭐", "🎊", "✨"];
+
 pub const ANIM_TYPE_NAMES: &[&str] = &[
-    "confetti", "cannon", "pop", "fireworks", "snow", "rain", "sparkle", "drop",
+    "confetti", "cannon", "pop", "fireworks", "snow", "rain", "sparkle", "drop", "fountain", "meteors", "vortex", "coins",
 ];
 
 pub const BUILTIN_PROFILE_NAMES: &[&str] = &[
@@ -44,6 +158,10 @@ impl AnimType {
             "rain" => Some(Self::Rain),
             "sparkle" => Some(Self::Sparkle),
             "drop" => Some(Self::Drop),
+            "fountain" => Some(Self::Fountain),
+            "meteors" => Some(Self::Meteors),
+            "vortex" => Some(Self::Vortex),
+            "coins" => Some(Self::Coins),
             _ => None,
         }
     }
@@ -59,13 +177,27 @@ impl AnimType {
             Self::Rain     => (800, 4.0, 200.0, 0.999, 1500.0, 3000.0, 20.0, 0.3),
             Self::Sparkle  => (100, 4.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.5),
             Self::Drop     => (1500, 3.0, 600.0, 0.99, 50.0, 200.0, 400.0, 0.5),
+            Self::Fountain => (1200, 4.0, 1200.0, 0.99, 1100.0, 1500.0, 20.0, 0.5),
+            Self::Meteors  => (40, 5.0, 60.0, 1.0, 1400.0, 2200.0, 0.0, 0.6),
+            Self::Vortex   => (800, 4.0, 0.0, 1.0, 40.0, 120.0, 0.0, 0.6),
+            Self::Coins    => (300, 4.0, 900.0, 0.995, 50.0, 250.0, 60.0, 0.5),
+        }
+    }
+
+    /// Seconds of motion a trailing streak covers; 0 draws no trail
+    pub fn default_trail(self) -> f64 {
+        match self {
+            Self::Meteors => 0.12,
+            Self::Fireworks => 0.05,
+            _ => 0.0,
         }
     }
 
     pub fn default_shape(self) -> Shape {
         match self {
-            Self::Fireworks | Self::Snow | Self::Sparkle => Shape::Circle,
-            Self::Drop | Self::Pop => Shape::Mixed,
+            Self::Fireworks | Self::Snow | Self::Sparkle | Self::Fountain | Self::Meteors
+                | Self::Coins => Shape::Circle,
+            Self::Drop | Self::Pop | Self::Vortex => Shape::Mixed,
             Self::Rain => Shape::Rect,
             _ => Shape::Rect,
         }
@@ -85,6 +217,12 @@ impl AnimType {
             Self::Fireworks => &[
                 [1.0, 0.27, 0.27], [1.0, 0.67, 0.0], [1.0, 1.0, 0.27], [1.0, 1.0, 1.0], [1.0, 0.42, 0.18],
             ],
+            Self::Coins => &[
+                [1.0, 0.84, 0.0], [1.0, 0.72, 0.0], [0.85, 0.65, 0.13], [1.0, 0.88, 0.40],
+            ],
+            Self::Meteors => &[
+                [1.0, 1.0, 1.0], [0.85, 0.92, 1.0], [1.0, 0.95, 0.80], [0.80, 0.88, 1.0], [1.0, 0.88, 0.75],
+            ],
             _ => &DEFAULT_COLORS,
         }
     }
@@ -96,7 +234,218 @@ impl Shape {
             "rect" => Some(Self::Rect),
             "circle" => Some(Self::Circle),
             "mixed" => Some(Self::Mixed),
+            "star" => Some(Self::Star),
+            "heart" => Some(Self::Heart),
+            "triangle" => Some(Self::Triangle),
+            "diamond" => Some(Self::Diamond),
+            "ribbon" => Some(Self::Ribbon),
+            "glyph" => Some(Self::Glyph),
+            "sprite" => Some(Self::Sprite),
+            "streamer" => Some(Self::Streamer),
             _ => None,
         }
     }
 }
+
+impl Axis {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "center" => Some(Self::Center),
+            "vertical" => Some(Self::Vertical),
+            _ => None,
+        }
+    }
+}
+
+impl Form {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "assemble" => Some(Self::Assemble),
+            "burst" => Some(Self::Burst),
+            _ => None,
+        }
+    }
+}
+
+impl Blend {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "normal" => Some(Self::Normal),
+            "additive" => Some(Self::Additive),
+            "screen" => Some(Self::Screen),
+            _ => None,
+        }
+    }
+}
+
+impl Backend {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "gsk" => Some(Self::Gsk),
+            "gl" => Some(Self::Gl),
+            _ => None,
+        }
+    }
+}
+
+impl Output {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "" => None,
+            "all" => Some(Self::All),
+            "focused" => Some(Self::Focused),
+            "pointer" => Some(Self::Pointer),
+            _ => Some(s.parse().map_or_else(|_| Self::Connector(s.to_string()), Self::Index)),
+        }
+    }
+}
+
+impl Coord {
+    /// `0.25` and `25%` are fractions, `300` and `1px` pixels; plain numbers above 1 are pixels.
+    pub fn from_str(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if let Some(px) = s.strip_suffix("px") {
+            return px.trim().parse().ok().map(Self::Px);
+        }
+        if let Some(pct) = s.strip_suffix('%') {
+            return pct.trim().parse::<f64>().ok().map(|p| Self::Frac(p / 100.0));
+        }
+        let v: f64 = s.parse().ok()?;
+        Some(if (0.0..=1.0).contains(&v) { Self::Frac(v) } else { Self::Px(v) })
+    }
+
+    /// Position along an axis `len` pixels long.
+    pub fn resolve(self, len: f64) -> f64 {
+        match self {
+            Self::Frac(f) => f * len,
+            Self::Px(px) => px,
+        }
+    }
+}
+
+/// Screen fractions named by `center`, a side (`top`) or a corner (`bottom-left`).
+fn anchor(s: &str) -> Option<[f64; 2]> {
+    match s {
+        "center" => Some([0.5, 0.5]),
+        "top" => Some([0.5, 0.0]),
+        "bottom" => Some([0.5, 1.0]),
+        "left" => Some([0.0, 0.5]),
+        "right" => Some([1.0, 0.5]),
+        "top-left" => Some([0.0, 0.0]),
+        "top-right" => Some([1.0, 0.0]),
+        "bottom-left" => Some([0.0, 1.0]),
+        "bottom-right" => Some([1.0, 1.0]),
+        _ => None,
+    }
+}
+
+impl Origin {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "cursor" => return Some(Self::Cursor),
+            "window" => return Some(Self::Window),
+            _ => {}
+        }
+        if let Some([x, y]) = anchor(s) {
+            return Some(Self::Point(Coord::Frac(x), Coord::Frac(y)));
+        }
+        let (x, y) = s.split_once(',')?;
+        Some(Self::Point(Coord::from_str(x)?, Coord::from_str(y)?))
+    }
+}
+
+impl Region {
+    pub fn from_str(s: &str) -> Option<Self> {
+        if s == "window" { return Some(Self::Window); }
+        let parts: Vec<&str> = s.split(',').map(str::trim).collect();
+        match parts[..] {
+            [a, w, h] => Some(Self::Anchored(anchor(a)?, Coord::from_str(w)?, Coord::from_str(h)?)),
+            [x, y, w, h] => Some(Self::Rect([
+                Coord::from_str(x)?, Coord::from_str(y)?, Coord::from_str(w)?, Coord::from_str(h)?,
+            ])),
+            _ => None,
+        }
+    }
+
+    /// Left, top, width and height inside a `w` x `h` monitor, clipped to it. `Window`
+    /// has no size of its own here and covers the whole monitor.
+    pub fn resolve(self, w: f64, h: f64) -> [f64; 4] {
+        let [x, y, rw, rh] = match self {
+            Self::Window => [0.0, 0.0, w, h],
+            Self::Rect([x, y, rw, rh]) => [x.resolve(w), y.resolve(h), rw.resolve(w), rh.resolve(h)],
+            Self::Anchored([ax, ay], rw, rh) => {
+                let (rw, rh) = (rw.resolve(w).min(w), rh.resolve(h).min(h));
+                [ax * (w - rw), ay * (h - rh), rw, rh]
+            }
+        };
+        let (x, y) = (x.clamp(0.0, w), y.clamp(0.0, h));
+        [x, y, rw.min(w - x), rh.min(h - y)]
+    }
+}
+
+impl Rainbow {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "time" => Some(Self::Time),
+            "position" => Some(Self::Position),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn coord_fractions_and_pixels() {
+        assert_eq!(Coord::from_str("0.25"), Some(Coord::Frac(0.25)));
+        assert_eq!(Coord::from_str("1"), Some(Coord::Frac(1.0)));
+        assert_eq!(Coord::from_str("25%"), Some(Coord::Frac(0.25)));
+        assert_eq!(Coord::from_str(" 300 "), Some(Coord::Px(300.0)));
+        assert_eq!(Coord::from_str("1px"), Some(Coord::Px(1.0)));
+        assert_eq!(Coord::from_str("-40"), Some(Coord::Px(-40.0)));
+        assert_eq!(Coord::from_str("left"), None);
+        assert_eq!(Coord::Frac(0.5).resolve(1920.0), 960.0);
+        assert_eq!(Coord::Px(300.0).resolve(1920.0), 300.0);
+    }
+
+    #[test]
+    fn origin_forms() {
+        use Coord::*;
+        assert_eq!(Origin::from_str("cursor"), Some(Origin::Cursor));
+        assert_eq!(Origin::from_str("window"), Some(Origin::Window));
+        assert_eq!(Origin::from_str("center"), Some(Origin::Point(Frac(0.5), Frac(0.5))));
+        assert_eq!(Origin::from_str("bottom-left"), Some(Origin::Point(Frac(0.0), Frac(1.0))));
+        assert_eq!(Origin::from_str("0.5,1.0"), Some(Origin::Point(Frac(0.5), Frac(1.0))));
+        assert_eq!(Origin::from_str("300, 200"), Some(Origin::Point(Px(300.0), Px(200.0))));
+        assert_eq!(Origin::from_str("middle"), None);
+        assert_eq!(Origin::from_str("1,2,3"), None);
+    }
+
+    #[test]
+    fn region_forms() {
+        use Coord::*;
+        assert_eq!(Region::from_str("window"), Some(Region::Window));
+        assert_eq!(Region::from_str("0,0,0.5,1"), Some(Region::Rect([Frac(0.0), Frac(0.0), Frac(0.5), Frac(1.0)])));
+        assert_eq!(Region::from_str("100, 50, 800px, 600"), Some(Region::Rect([Px(100.0), Px(50.0), Px(800.0), Px(600.0)])));
+        assert_eq!(Region::from_str("bottom-right,400,300"), Some(Region::Anchored([1.0, 1.0], Px(400.0), Px(300.0))));
+        assert_eq!(Region::from_str("middle,400,300"), None);
+        assert_eq!(Region::from_str("0.5,0.5"), None);
+        assert_eq!(Region::from_str("0,0,wide,1"), None);
+    }
+
+    #[test]
+    fn region_resolve_clips_to_monitor() {
+        use Coord::*;
+        let rect = |x, y, w, h| Region::Rect([x, y, w, h]);
+        assert_eq!(rect(Frac(0.5), Frac(0.0), Frac(0.5), Frac(1.0)).resolve(1920.0, 1080.0), [960.0, 0.0, 960.0, 1080.0]);
+        assert_eq!(rect(Px(1800.0), Px(0.0), Px(400.0), Px(200.0)).resolve(1920.0, 1080.0), [1800.0, 0.0, 120.0, 200.0]);
+        assert_eq!(rect(Px(-50.0), Px(5000.0), Px(100.0), Px(100.0)).resolve(1920.0, 1080.0), [0.0, 1080.0, 100.0, 0.0]);
+        let anchored = |a, w, h| Region::Anchored(a, w, h);
+        assert_eq!(anchored([0.5, 0.5], Px(400.0), Px(200.0)).resolve(1000.0, 1000.0), [300.0, 400.0, 400.0, 200.0]);
+        assert_eq!(anchored([1.0, 1.0], Frac(0.25), Frac(0.5)).resolve(1000.0, 800.0), [750.0, 400.0, 250.0, 400.0]);
+        assert_eq!(anchored([0.0, 1.0], Px(5000.0), Px(100.0)).resolve(1000.0, 800.0), [0.0, 700.0, 1000.0, 100.0]);
+        assert_eq!(Region::Window.resolve(1920.0, 1080.0), [0.0, 0.0, 1920.0, 1080.0]);
+    }
+}